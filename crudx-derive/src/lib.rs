@@ -0,0 +1,197 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    ext::IdentExt, parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, LitStr, Result,
+};
+
+/// implement the entity contract of crudx, fields can be indexed and the struct
+/// lists its name and field names, the declared mappings are applied by
+/// `Model::of` and `OwnedModel::of` but not by `new`
+/// # Example
+/// ```ignore
+/// #[derive(Clone, Default, Entity)]
+/// #[crudx(table = "audit.oplog")]
+/// pub struct Oplog {
+///     #[crudx(key)]
+///     pub pid: i64,
+///     #[crudx(rename = "user")]
+///     pub user_id: String,
+///     #[crudx(read_only)]
+///     pub optime: String,
+///     #[crudx(skip)]
+///     pub remark: String,
+///     #[crudx(version)]
+///     pub version: i32,
+///     #[crudx(redact)]
+///     pub token: String,
+/// }
+/// ```
+#[proc_macro_derive(Entity, attributes(crudx))]
+pub fn derive_entity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// declared mappings of a field
+#[derive(Default)]
+struct FieldAttr {
+    rename: Option<String>,
+    skip: bool,
+    read_only: bool,
+    key: bool,
+    version: bool,
+    redact: bool,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let ident = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(ident, "Entity needs named fields")),
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                ident,
+                "Entity can only be derived for structs",
+            ))
+        }
+    };
+    let table = table_attr(&input.attrs)?;
+
+    let mut members = Vec::new();
+    let mut names = Vec::new();
+    let mut columns = Vec::new();
+    let mut roles = Vec::new();
+    let mut keys = Vec::new();
+    let mut version = None;
+    let mut redacts = Vec::new();
+    for field in fields {
+        let member = field.ident.as_ref().unwrap();
+        let name = member.unraw().to_string();
+        let attr = field_attr(&field.attrs)?;
+
+        if attr.skip {
+            columns.push(quote!((#name, "-")));
+        } else if let Some(co) = &attr.rename {
+            columns.push(quote!((#name, #co)));
+        }
+        if attr.read_only {
+            roles.push(quote!((#name, ::crudx::model::Role::ReadOnly)));
+        }
+        if attr.key {
+            keys.push(quote!(#name));
+        }
+        if attr.redact {
+            redacts.push(quote!(#name));
+        }
+        if attr.version {
+            if version.is_some() {
+                return Err(Error::new_spanned(
+                    member,
+                    "only one version field is allowed",
+                ));
+            }
+            version = Some(name.clone());
+        }
+        members.push(member);
+        names.push(name);
+    }
+
+    let indexes = 0..members.len();
+    let indexes_mut = indexes.clone();
+    let struct_name = ident.to_string();
+    let table = match table {
+        Some(table) => quote!(Some(#table)),
+        None => quote!(None),
+    };
+    let version = match version {
+        Some(version) => quote!(Some(#version)),
+        None => quote!(None),
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::std::ops::Index<usize> for #ident #ty_generics #where_clause {
+            type Output = dyn ::std::any::Any;
+
+            fn index(&self, index: usize) -> &Self::Output {
+                match index {
+                    #(#indexes => &self.#members,)*
+                    _ => panic!("{} has no field {}", #struct_name, index),
+                }
+            }
+        }
+
+        impl #impl_generics ::std::ops::IndexMut<usize> for #ident #ty_generics #where_clause {
+            fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+                match index {
+                    #(#indexes_mut => &mut self.#members,)*
+                    _ => panic!("{} has no field {}", #struct_name, index),
+                }
+            }
+        }
+
+        impl #impl_generics ::std::ops::Not for &#ident #ty_generics #where_clause {
+            type Output = (&'static str, &'static [&'static str]);
+
+            fn not(self) -> Self::Output {
+                (#struct_name, &[#(#names),*])
+            }
+        }
+
+        impl #impl_generics ::crudx::model::Entity for #ident #ty_generics #where_clause {
+            const TABLE: Option<&'static str> = #table;
+            const COLUMNS: &'static [(&'static str, &'static str)] = &[#(#columns),*];
+            const ROLES: &'static [(&'static str, ::crudx::model::Role)] = &[#(#roles),*];
+            const KEYS: &'static [&'static str] = &[#(#keys),*];
+            const VERSION: Option<&'static str> = #version;
+            const REDACT: &'static [&'static str] = &[#(#redacts),*];
+        }
+    })
+}
+
+/// `#[crudx(table = "...")]` of the struct
+fn table_attr(attrs: &[Attribute]) -> Result<Option<String>> {
+    let mut table = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("crudx")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unknown crudx attribute of the struct"))
+            }
+        })?;
+    }
+    Ok(table)
+}
+
+/// `#[crudx(rename = "...", skip, read_only, key, version, redact)]` of a field
+fn field_attr(attrs: &[Attribute]) -> Result<FieldAttr> {
+    let mut field = FieldAttr::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("crudx")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                field.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("skip") {
+                field.skip = true;
+            } else if meta.path.is_ident("read_only") {
+                field.read_only = true;
+            } else if meta.path.is_ident("key") {
+                field.key = true;
+            } else if meta.path.is_ident("version") {
+                field.version = true;
+            } else if meta.path.is_ident("redact") {
+                field.redact = true;
+            } else {
+                return Err(meta.error("unknown crudx attribute of the field"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(field)
+}
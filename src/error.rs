@@ -0,0 +1,366 @@
+use std::{error, fmt};
+
+/// the version of the updated data has been changed by others
+#[derive(Debug)]
+pub struct StaleEntity {
+    /// database table name
+    pub table: String,
+}
+
+impl fmt::Display for StaleEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` data has been modified or deleted by others, version check failed",
+            self.table
+        )
+    }
+}
+
+impl error::Error for StaleEntity {}
+
+/// expected number of rows changed by a write
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RowCount {
+    /// exactly this number of rows
+    Exactly(u64),
+    /// no more than this number of rows
+    AtMost(u64),
+}
+
+impl RowCount {
+    /// whether the number of changed rows is expected
+    pub fn allows(&self, rows: u64) -> bool {
+        match *self {
+            RowCount::Exactly(n) => rows == n,
+            RowCount::AtMost(n) => rows <= n,
+        }
+    }
+}
+
+impl fmt::Display for RowCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RowCount::Exactly(n) => write!(f, "exactly {}", n),
+            RowCount::AtMost(n) => write!(f, "at most {}", n),
+        }
+    }
+}
+
+/// the write changed an unexpected number of rows and has been rolled back
+#[derive(Debug)]
+pub struct RowCountMismatch {
+    /// database table name
+    pub table: String,
+    /// expected number of rows
+    pub expected: RowCount,
+    /// number of rows the write changed
+    pub actual: u64,
+}
+
+impl fmt::Display for RowCountMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` expected {} rows to be changed but {} rows were, the change has been rolled back",
+            self.table, self.expected, self.actual
+        )
+    }
+}
+
+impl error::Error for RowCountMismatch {}
+
+/// the statement took longer than the timeout of the model
+#[derive(Debug)]
+pub struct Timeout {
+    /// sql of the statement
+    pub sql: String,
+    /// the connection was left in the middle of a response, it must be
+    /// discarded instead of being used again
+    pub discard: bool,
+}
+
+impl Timeout {
+    #[cfg_attr(
+        not(any(
+            feature = "postgres",
+            feature = "mysql",
+            feature = "sqlite",
+            feature = "mssql"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn new(sql: impl fmt::Display) -> Self {
+        Timeout {
+            sql: sql.to_string(),
+            discard: false,
+        }
+    }
+
+    /// timeout that left the connection unusable
+    #[cfg_attr(not(feature = "mssql"), allow(dead_code))]
+    pub(crate) fn discarded(mut self) -> Self {
+        self.discard = true;
+        self
+    }
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sql:`{}`  statement timed out", self.sql)?;
+        if self.discard {
+            write!(f, ", the connection must be discarded")?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for Timeout {}
+
+/// error of crudx returned by every method, it converts to and from `anyhow::Error`
+/// # Example
+/// ```no_run
+/// match Model::new(&oplog).bind(&pool).insert_one(None).await {
+///     Ok(_) => {}
+///     Err(crudx::Error::UniqueViolation { constraint, .. }) => println!("{:?} exists", constraint),
+///     Err(crudx::Error::StaleEntity(_)) => println!("modified by others"),
+///     Err(err) => return Err(err.into()),
+/// }
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// the query found no data
+    NotFound,
+    /// the quantity of ? is not equal to the number of args
+    ArgCountMismatch,
+    /// a value cannot be converted between rust and the database, the field is
+    /// none for an argument and the type is none when the conversion function does
+    /// not handle the value, an argument has the sql built up to it
+    Conversion {
+        field: Option<String>,
+        ty: Option<&'static str>,
+        source: Option<Box<dyn error::Error + Send + Sync>>,
+        sql: Option<String>,
+    },
+    /// a unique constraint or index is violated
+    UniqueViolation {
+        constraint: Option<String>,
+        sql: String,
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+    /// a foreign key constraint is violated
+    ForeignKeyViolation {
+        constraint: Option<String>,
+        sql: String,
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+    /// a not null constraint is violated
+    NotNullViolation {
+        constraint: Option<String>,
+        sql: String,
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+    /// a check constraint is violated
+    CheckViolation {
+        constraint: Option<String>,
+        sql: String,
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+    /// see [`Timeout`]
+    Timeout(Timeout),
+    /// see [`StaleEntity`]
+    StaleEntity(StaleEntity),
+    /// see [`RowCountMismatch`]
+    RowCountMismatch(RowCountMismatch),
+    /// any other error of the database
+    Database {
+        sql: String,
+        args: String,
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+    /// any other error
+    Other(anyhow::Error),
+}
+
+impl Error {
+    /// conversion error of a column to a rust type, used by the conversion macros
+    pub fn conversion<E>(field: &str, ty: &'static str, err: E) -> Self
+    where
+        E: error::Error + Send + Sync + 'static,
+    {
+        Error::Conversion {
+            field: Some(field.to_string()),
+            ty: Some(ty),
+            source: Some(Box::new(err)),
+            sql: None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound => write!(f, "no data found"),
+            Error::ArgCountMismatch => write!(
+                f,
+                "the quantity of ? is not equal to the number of parameters"
+            ),
+            Error::Conversion {
+                field,
+                ty,
+                source,
+                sql,
+            } => {
+                if let Some(sql) = sql {
+                    write!(f, "sql:`{}`  ", sql)?;
+                }
+                match (field, ty) {
+                    (Some(field), Some(ty)) => {
+                        write!(f, "`{}` cannot be converted to {}", field, ty)?
+                    }
+                    (Some(field), None) => write!(
+                        f,
+                        "`{}` cannot be converted please customize the function from_row",
+                        field
+                    )?,
+                    (None, _) => write!(
+                        f,
+                        "arguments cannot be converted please customize the function to_arg"
+                    )?,
+                }
+                match source {
+                    Some(source) => write!(f, "  {}", source),
+                    None => Ok(()),
+                }
+            }
+            Error::UniqueViolation { sql, source, .. }
+            | Error::ForeignKeyViolation { sql, source, .. }
+            | Error::NotNullViolation { sql, source, .. }
+            | Error::CheckViolation { sql, source, .. } => write!(f, "sql:`{}`  {}", sql, source),
+            Error::Timeout(err) => err.fmt(f),
+            Error::StaleEntity(err) => err.fmt(f),
+            Error::RowCountMismatch(err) => err.fmt(f),
+            Error::Database { sql, args, source } => {
+                write!(f, "sql:`{}` args:[{}]  {}", sql, args, source)
+            }
+            Error::Other(err) => err.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Conversion {
+                source: Some(source),
+                ..
+            } => Some(&**source),
+            Error::UniqueViolation { source, .. }
+            | Error::ForeignKeyViolation { source, .. }
+            | Error::NotNullViolation { source, .. }
+            | Error::CheckViolation { source, .. }
+            | Error::Database { source, .. } => Some(&**source),
+            Error::Other(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// result of crudx
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Other(err.into())
+    }
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+impl From<sqlx::Error> for Error {
+    fn from(err: sqlx::Error) -> Self {
+        Error::Other(err.into())
+    }
+}
+
+#[cfg(feature = "mssql")]
+impl From<tiberius::error::Error> for Error {
+    fn from(err: tiberius::error::Error) -> Self {
+        Error::Other(err.into())
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<Error>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<Timeout>() {
+            Ok(err) => return Error::Timeout(err),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<StaleEntity>() {
+            Ok(err) => return Error::StaleEntity(err),
+            Err(err) => err,
+        };
+        match err.downcast::<RowCountMismatch>() {
+            Ok(err) => Error::RowCountMismatch(err),
+            Err(err) => Error::Other(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downcast() {
+        let err: anyhow::Error = Error::StaleEntity(StaleEntity {
+            table: "oplog".to_string(),
+        })
+        .into();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::StaleEntity(stale)) if stale.table == "oplog"
+        ));
+        assert!(matches!(Error::from(err), Error::StaleEntity(_)));
+
+        let io = std::io::Error::new(std::io::ErrorKind::TimedOut, "canceled");
+        let err = anyhow::Error::new(io).context(Error::Timeout(Timeout::new("select 1")));
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::Timeout(timeout)) if timeout.sql == "select 1"
+        ));
+        assert!(err.downcast_ref::<std::io::Error>().is_some());
+    }
+
+    #[test]
+    fn from_anyhow() {
+        let err = anyhow::Error::new(RowCountMismatch {
+            table: "oplog".to_string(),
+            expected: RowCount::Exactly(1),
+            actual: 2,
+        });
+        assert!(matches!(Error::from(err), Error::RowCountMismatch(m) if m.actual == 2));
+        assert!(matches!(
+            Error::from(anyhow::anyhow!("other")),
+            Error::Other(_)
+        ));
+    }
+
+    #[test]
+    fn conversion_display() {
+        let err = Error::Conversion {
+            field: None,
+            ty: None,
+            source: None,
+            sql: Some("select * from oplog where pid=".to_string()),
+        };
+        assert_eq!(
+            err.to_string(),
+            "sql:`select * from oplog where pid=`  arguments cannot be converted please customize the function to_arg"
+        );
+    }
+}
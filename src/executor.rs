@@ -1,8 +1,9 @@
 #[cfg(not(feature = "async_trait"))]
 use std::future::Future;
 
-use crate::{Filter, Result};
+use crate::{anyhow, Filter, Result};
 
+#[derive(Clone, Copy)]
 pub struct Other<'a> {
     /// join on statement section, need to have join on
     /// # Example
@@ -24,6 +25,53 @@ pub struct Other<'a> {
     pub having: &'a str,
 }
 
+/// select source of an insert, built from another model
+/// # Example
+/// ```no_run
+/// let source: Source = Model::new(&Oplog::default()).into();
+/// let res = Model::new(&OplogArchive::default())
+///     .bind(&pool)
+///     .insert_select(source, &Filter::default().and(expr!(oplog.pid < 100)))
+///     .await
+///     .unwrap();
+/// ```
+#[cfg_attr(
+    not(any(
+        feature = "postgres",
+        feature = "mysql",
+        feature = "sqlite",
+        feature = "mssql"
+    )),
+    allow(dead_code)
+)]
+pub struct Source<'a> {
+    /// database table name
+    pub(crate) table: String,
+    /// field name and select expression
+    pub(crate) columns: Vec<(&'a str, &'a str)>,
+    /// soft delete column and comparison
+    pub(crate) scope: Option<(&'a str, String)>,
+}
+
+#[cfg_attr(
+    not(any(
+        feature = "postgres",
+        feature = "mysql",
+        feature = "sqlite",
+        feature = "mssql"
+    )),
+    allow(dead_code)
+)]
+impl<'a> Source<'a> {
+    /// select expression of a field
+    pub(crate) fn column(&self, fd: &str) -> Result<&'a str> {
+        match self.columns.iter().find(|(name, _)| *name == fd) {
+            Some((_, co)) => Ok(co),
+            None => Err(anyhow!("`{}` not found in `{}`", fd, self.table).into()),
+        }
+    }
+}
+
 /// ### insert_one
 /// ```no_run
 /// let clazz = Clazz {
@@ -31,7 +79,7 @@ pub struct Other<'a> {
 ///     name: "one".to_string(),
 /// };
 /// let mut model = Model::new(&clazz);
-/// *model.fields.get_mut(field!(clazz.id)).unwrap() = "-";
+/// model.fields.insert(field!(clazz.id), "-");
 /// let res = model
 ///     .bind(&pool)
 ///     .insert_one(Some(&Filter::default().and((
@@ -45,7 +93,7 @@ pub struct Other<'a> {
 /// ```no_run
 /// let clazz = Clazz::default();
 /// let mut model = Model::new(&clazz);
-/// *model.fields.get_mut(field!(clazz.id)).unwrap() = "-";
+/// model.fields.insert(field!(clazz.id), "-");
 /// let res = model
 ///     .bind(&pool)
 ///     .insert(&vec![
@@ -72,13 +120,32 @@ pub struct Other<'a> {
 ///     name: "five".to_string(),
 /// };
 /// let mut model = Model::new(&clazz);
-/// *model.fields.get_mut(field!(clazz.id)).unwrap() = "-";
+/// model.fields.insert(field!(clazz.id), "-");
 /// let res = model
 ///     .bind(&pool)
 ///     .update(Filter::default().and(expr!(clazz.id > 5)))
 ///     .await
 ///     .unwrap();
 /// ```
+/// ### update_many
+/// ```no_run
+/// let clazz = Clazz::default();
+/// let clazzes = vec![
+///     Clazz {
+///         id: 1,
+///         name: "one".to_string(),
+///     },
+///     Clazz {
+///         id: 2,
+///         name: "two".to_string(),
+///     },
+/// ];
+/// let res = Model::new(&clazz)
+///     .bind(&pool)
+///     .update_many(&clazzes, &[field!(clazz.id)])
+///     .await
+///     .unwrap();
+/// ```
 /// ### delete
 /// ```no_run
 /// let clazz = Clazz::default();
@@ -88,6 +155,43 @@ pub struct Other<'a> {
 ///     .await
 ///     .unwrap();
 /// ```
+/// ### delete_returning
+/// ```no_run
+/// let clazz = Clazz::default();
+/// let res = Model::new(&clazz)
+///     .bind(&pool)
+///     .delete_returning(Filter::default().and(expr!(clazz.name = "five")))
+///     .await
+///     .unwrap();
+/// ```
+/// ### delete_all
+/// ```no_run
+/// let res = Model::new(&Clazz::default())
+///     .bind(&pool)
+///     .delete_all()
+///     .await
+///     .unwrap();
+/// ```
+/// ### find_by_id
+/// ```no_run
+/// let clazz = Clazz {
+///     id: 3,
+///     ..Default::default()
+/// };
+/// let mut model = Model::new(&clazz);
+/// model.keys = vec![field!(clazz.id)];
+/// let res = model.bind(&pool).find_by_id().await.unwrap();
+/// ```
+/// ### save
+/// ```no_run
+/// let clazz = Clazz {
+///     id: 0,
+///     name: "six".to_string(),
+/// };
+/// let mut model = Model::new(&clazz);
+/// model.keys = vec![field!(clazz.id)];
+/// let res = model.bind(&pool).save().await.unwrap();
+/// ```
 /// ### count
 /// ```no_run
 /// let res = Model::new(&Clazz::default())
@@ -119,20 +223,81 @@ pub trait Executor<'a, T> {
     #[cfg(not(feature = "async_trait"))]
     fn insert(self, data: &'a [T]) -> impl Future<Output = Result<u64>> + Send;
 
-    /// update eligible data to the database
+    /// update eligible data to the database, an empty filter is refused
     #[cfg(feature = "async_trait")]
     async fn update(self, filter: &'a Filter) -> Result<u64>;
-    /// update eligible data to the database
+    /// update eligible data to the database, an empty filter is refused
     #[cfg(not(feature = "async_trait"))]
     fn update(self, filter: &'a Filter) -> impl Future<Output = Result<u64>> + Send;
 
-    /// delete data from the database that meets the criteria
+    /// insert the data selected from another model, columns are matched by field name
+    #[cfg(feature = "async_trait")]
+    async fn insert_select(self, source: Source<'a>, filter: &'a Filter) -> Result<u64>;
+    /// insert the data selected from another model, columns are matched by field name
+    #[cfg(not(feature = "async_trait"))]
+    fn insert_select(
+        self,
+        source: Source<'a>,
+        filter: &'a Filter,
+    ) -> impl Future<Output = Result<u64>> + Send;
+
+    /// update data and return the updated data, an empty filter is refused
+    #[cfg(feature = "async_trait")]
+    async fn update_returning(self, filter: &'a Filter) -> Result<Vec<T>>;
+    /// update data and return the updated data, an empty filter is refused
+    #[cfg(not(feature = "async_trait"))]
+    fn update_returning(self, filter: &'a Filter) -> impl Future<Output = Result<Vec<T>>> + Send;
+
+    /// update all data in the database table
+    #[cfg(feature = "async_trait")]
+    async fn update_all(self) -> Result<u64>;
+    /// update all data in the database table
+    #[cfg(not(feature = "async_trait"))]
+    fn update_all(self) -> impl Future<Output = Result<u64>> + Send;
+
+    /// update some data to the database by key fields, the data is split into
+    /// statements within the parameter limit of the backend run in one
+    /// transaction, return the number of rows affected by each batch
+    #[cfg(feature = "async_trait")]
+    async fn update_many(self, data: &'a [T], keys: &'a [&'a str]) -> Result<Vec<u64>>;
+    /// update some data to the database by key fields, the data is split into
+    /// statements within the parameter limit of the backend run in one
+    /// transaction, return the number of rows affected by each batch
+    #[cfg(not(feature = "async_trait"))]
+    fn update_many(
+        self,
+        data: &'a [T],
+        keys: &'a [&'a str],
+    ) -> impl Future<Output = Result<Vec<u64>>> + Send;
+
+    /// delete data from the database that meets the criteria, an empty filter is refused
     #[cfg(feature = "async_trait")]
     async fn delete(self, filter: &'a Filter) -> Result<u64>;
-    /// delete data from the database that meets the criteria
+    /// delete data from the database that meets the criteria, an empty filter is refused
     #[cfg(not(feature = "async_trait"))]
     fn delete(self, filter: &'a Filter) -> impl Future<Output = Result<u64>> + Send;
 
+    /// delete data and return the deleted data, an empty filter is refused
+    #[cfg(feature = "async_trait")]
+    async fn delete_returning(self, filter: &'a Filter) -> Result<Vec<T>>;
+    /// delete data and return the deleted data, an empty filter is refused
+    #[cfg(not(feature = "async_trait"))]
+    fn delete_returning(self, filter: &'a Filter) -> impl Future<Output = Result<Vec<T>>> + Send;
+
+    /// delete all data in the database table
+    #[cfg(feature = "async_trait")]
+    async fn delete_all(self) -> Result<u64>;
+    /// delete all data in the database table
+    #[cfg(not(feature = "async_trait"))]
+    fn delete_all(self) -> impl Future<Output = Result<u64>> + Send;
+
+    /// physically delete data that meets the criteria even if the model is soft deleted
+    #[cfg(feature = "async_trait")]
+    async fn hard_delete(self, filter: &'a Filter) -> Result<u64>;
+    /// physically delete data that meets the criteria even if the model is soft deleted
+    #[cfg(not(feature = "async_trait"))]
+    fn hard_delete(self, filter: &'a Filter) -> impl Future<Output = Result<u64>> + Send;
+
     /// obtain the number of database data records
     #[cfg(feature = "async_trait")]
     async fn count(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<i64>;
@@ -144,6 +309,41 @@ pub trait Executor<'a, T> {
         other: Option<Other<'a>>,
     ) -> impl Future<Output = Result<i64>> + Send;
 
+    /// query the data with the key values of the entity
+    #[cfg(feature = "async_trait")]
+    async fn find_by_id(self) -> Result<T>;
+    /// query the data with the key values of the entity
+    #[cfg(not(feature = "async_trait"))]
+    fn find_by_id(self) -> impl Future<Output = Result<T>> + Send;
+
+    /// determine if the data with the key values of the entity exists
+    #[cfg(feature = "async_trait")]
+    async fn exists_by_id(self) -> Result<bool>;
+    /// determine if the data with the key values of the entity exists
+    #[cfg(not(feature = "async_trait"))]
+    fn exists_by_id(self) -> impl Future<Output = Result<bool>> + Send;
+
+    /// update the data with the key values of the entity
+    #[cfg(feature = "async_trait")]
+    async fn update_by_id(self) -> Result<u64>;
+    /// update the data with the key values of the entity
+    #[cfg(not(feature = "async_trait"))]
+    fn update_by_id(self) -> impl Future<Output = Result<u64>> + Send;
+
+    /// delete the data with the key values of the entity
+    #[cfg(feature = "async_trait")]
+    async fn delete_by_id(self) -> Result<u64>;
+    /// delete the data with the key values of the entity
+    #[cfg(not(feature = "async_trait"))]
+    fn delete_by_id(self) -> impl Future<Output = Result<u64>> + Send;
+
+    /// insert the entity when its key values are unset, otherwise update it by id
+    #[cfg(feature = "async_trait")]
+    async fn save(self) -> Result<u64>;
+    /// insert the entity when its key values are unset, otherwise update it by id
+    #[cfg(not(feature = "async_trait"))]
+    fn save(self) -> impl Future<Output = Result<u64>> + Send;
+
     /// order by statement section
     /// !!please note that there is an injection risk when using upload fields
     fn order_by(self, order: &'a str) -> impl OrderExecutor<'a, T>;
@@ -153,7 +353,7 @@ pub trait Executor<'a, T> {
 /// ```no_run
 /// let student = Student::default();
 /// let mut model = Model::new(&student);
-/// *model.fields.get_mut(field!(student.clazz_name)).unwrap() = "-";
+/// model.fields.insert(field!(student.clazz_name), "-");
 /// let res = model
 ///     .bind(&pool)
 ///     .order_by(field!(student.id))
@@ -165,9 +365,9 @@ pub trait Executor<'a, T> {
 /// ```no_run
 /// let student = Student::default();
 /// let mut model = Model::new(&student);
-/// *model.fields.get_mut(field!(student.id)).unwrap() = "student.id";
-/// *model.fields.get_mut(field!(student.name)).unwrap() = "student.name";
-/// *model.fields.get_mut(field!(student.clazz_name)).unwrap() = "clazz.name";
+/// model.fields.insert(field!(student.id), "student.id");
+/// model.fields.insert(field!(student.name), "student.name");
+/// model.fields.insert(field!(student.clazz_name), "clazz.name");
 /// let res = model
 ///     .bind(&pool)
 ///     .order_by("student.id")
@@ -214,7 +414,7 @@ pub trait OrderExecutor<'a, T> {
 /// ```no_run
 /// let student = Student::default();
 /// let mut model = Model::new(&student);
-/// *model.fields.get_mut(field!(student.clazz_name)).unwrap() = "-";
+/// model.fields.insert(field!(student.clazz_name), "-");
 /// let res = model
 ///     .bind(&pool)
 ///     .order_by(field!(student.id))
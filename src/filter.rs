@@ -121,7 +121,7 @@ macro_rules! args {
 /// ```no_run
 /// assert_eq!(
 ///     expr!(student.name = "Alice"),
-///     (&"name = ?".to_string(), vec![Box::new("Alice")])
+///     (&"`name` = ?".to_string(), vec![Box::new("Alice")])
 /// );
 /// ```
 #[macro_export]
@@ -129,7 +129,7 @@ macro_rules! expr {
     ($struct_value:ident.$field:ident $op:tt $arg:expr) => {{
         let _ = &$struct_value.$field;
         (
-            &format!("{} {} ?", stringify!($field), stringify!($op)),
+            &format!("`{}` {} ?", stringify!($field), stringify!($op)),
             vec![Box::new($arg)],
         )
     }};
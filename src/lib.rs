@@ -1,10 +1,13 @@
-pub use anyhow::{anyhow, Result};
+pub use anyhow::anyhow;
+
+mod error;
+pub use error::{Error, Result, RowCount, RowCountMismatch, StaleEntity, Timeout};
 
 mod filter;
 pub use filter::Filter;
 
 mod executor;
-pub use executor::{Executor, LimitExecutor, OrderExecutor, Other};
+pub use executor::{Executor, LimitExecutor, OrderExecutor, Other, Source};
 
 #[cfg(any(
     feature = "postgres",
@@ -13,3 +16,21 @@ pub use executor::{Executor, LimitExecutor, OrderExecutor, Other};
     feature = "mssql"
 ))]
 pub mod model;
+
+#[cfg(all(
+    feature = "any",
+    any(
+        feature = "postgres",
+        feature = "mysql",
+        feature = "sqlite",
+        feature = "mssql"
+    )
+))]
+pub use model::{connect, AnyDb};
+#[cfg(any(
+    feature = "postgres",
+    feature = "mysql",
+    feature = "sqlite",
+    feature = "mssql"
+))]
+pub use model::{retry, transaction, transaction_retry, transaction_with, Isolation, Retry};
@@ -0,0 +1,119 @@
+use std::{future::Future, ops::DerefMut};
+
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+use sqlx::pool::PoolConnection;
+use sqlx::{Acquire, Connection, Database, Transaction};
+
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+use crate::anyhow;
+use crate::Result;
+
+/// executor bound to a sqlx model, an acquirable one opens the transactions of
+/// expected row counts, server side timeouts and batches and keeps one
+/// connection for retries
+pub(crate) trait Bound<'a, DB: Database>: Send + Sized {
+    type Executor: sqlx::Executor<'a, Database = DB>;
+    type Connection: DerefMut<Target = DB::Connection> + Send;
+
+    /// whether connections and transactions can be opened from the executor
+    fn acquirable(&self) -> bool;
+
+    /// executor running a statement
+    fn executor(self) -> Self::Executor;
+
+    /// connection of the executor, retried statements run on it
+    fn connection(self) -> impl Future<Output = Result<Self::Connection>> + Send;
+
+    /// transaction of the executor, a savepoint when one is active
+    fn transaction(self) -> impl Future<Output = Result<Transaction<'a, DB>>> + Send;
+}
+
+/// executor implementing only `Executor`, bound by `bind`
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+pub(crate) struct Plain<E>(pub(crate) E);
+
+/// executor implementing `Acquire` too, bound by `bind_acquire`
+pub(crate) struct Acquired<E>(pub(crate) E);
+
+/// error of a statement needing a transaction on a plain executor
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+const UNACQUIRABLE: &str =
+    "the statement runs in a transaction, its executor needs to be bound by bind_acquire";
+
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+impl<'a, DB, E> Bound<'a, DB> for Plain<E>
+where
+    DB: Database,
+    E: sqlx::Executor<'a, Database = DB>,
+{
+    type Executor = E;
+    type Connection = PoolConnection<DB>;
+
+    fn acquirable(&self) -> bool {
+        false
+    }
+
+    fn executor(self) -> E {
+        self.0
+    }
+
+    async fn connection(self) -> Result<Self::Connection> {
+        Err(anyhow!(UNACQUIRABLE).into())
+    }
+
+    async fn transaction(self) -> Result<Transaction<'a, DB>> {
+        Err(anyhow!(UNACQUIRABLE).into())
+    }
+}
+
+impl<'a, DB, E> Bound<'a, DB> for Acquired<E>
+where
+    DB: Database,
+    E: sqlx::Executor<'a, Database = DB> + Acquire<'a, Database = DB>,
+{
+    type Executor = E;
+    type Connection = E::Connection;
+
+    fn acquirable(&self) -> bool {
+        true
+    }
+
+    fn executor(self) -> E {
+        self.0
+    }
+
+    async fn connection(self) -> Result<Self::Connection> {
+        Ok(self.0.acquire().await?)
+    }
+
+    async fn transaction(self) -> Result<Transaction<'a, DB>> {
+        Ok(self.0.begin().await?)
+    }
+}
+
+/// connection of a transaction or a retried statement
+impl<'c, C> Bound<'c, C::Database> for &'c mut C
+where
+    C: Connection,
+    &'c mut C: sqlx::Executor<'c, Database = C::Database>
+        + Acquire<'c, Database = C::Database, Connection = &'c mut C>,
+{
+    type Executor = &'c mut C;
+    type Connection = &'c mut C;
+
+    fn acquirable(&self) -> bool {
+        true
+    }
+
+    fn executor(self) -> Self {
+        self
+    }
+
+    async fn connection(self) -> Result<Self> {
+        Ok(self)
+    }
+
+    async fn transaction(self) -> Result<Transaction<'c, C::Database>> {
+        Ok(Acquire::begin(self).await?)
+    }
+}
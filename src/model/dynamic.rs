@@ -0,0 +1,218 @@
+use std::{
+    any::Any,
+    future::Future,
+    ops::{IndexMut, Not},
+    pin::Pin,
+};
+
+use crate::{Filter, Other, Result};
+
+use super::{Backend, Repo};
+
+/// boxed future of a dyn compatible executor
+pub type BoxFuture<'r, O> = Pin<Box<dyn Future<Output = O> + Send + 'r>>;
+
+/// dyn compatible executor of an entity type, a `Repo` of any backend can be
+/// kept as `Box<dyn DynExecutor<T>>` and a fake can be injected in tests
+/// # Example
+/// ```no_run
+/// let db: Box<dyn DynExecutor<Oplog>> = match kind {
+///     "mysql" => Box::new(Repo::new(OwnedModel::new(Oplog::default()), mysql_pool)),
+///     _ => Box::new(Repo::new(OwnedModel::new(Oplog::default()), pg_pool)),
+/// };
+/// let res = db.query(field!(oplog.pid), Some((10, 0)), &Filter::default(), None).await?;
+/// ```
+pub trait DynExecutor<T>: Send {
+    /// insert an entity, it is only inserted when the filter holds
+    fn insert_one<'r>(
+        &'r mut self,
+        entity: &'r T,
+        filter: Option<&'r Filter>,
+    ) -> BoxFuture<'r, Result<u64>>;
+
+    /// insert some data
+    fn insert<'r>(&'r mut self, data: &'r [T]) -> BoxFuture<'r, Result<u64>>;
+
+    /// update data by an entity
+    fn update<'r>(&'r mut self, entity: &'r T, filter: &'r Filter) -> BoxFuture<'r, Result<u64>>;
+
+    /// update data by an entity and return the changed data
+    fn update_returning<'r>(
+        &'r mut self,
+        entity: &'r T,
+        filter: &'r Filter,
+    ) -> BoxFuture<'r, Result<Vec<T>>>;
+
+    /// update all data of the table by an entity
+    fn update_all<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<u64>>;
+
+    /// update some data by their keys
+    fn update_many<'r>(
+        &'r mut self,
+        data: &'r [T],
+        keys: &'r [&'r str],
+    ) -> BoxFuture<'r, Result<Vec<u64>>>;
+
+    /// delete data
+    fn delete<'r>(&'r mut self, filter: &'r Filter) -> BoxFuture<'r, Result<u64>>;
+
+    /// delete data and return the deleted data
+    fn delete_returning<'r>(&'r mut self, filter: &'r Filter) -> BoxFuture<'r, Result<Vec<T>>>;
+
+    /// delete all data of the table
+    fn delete_all(&mut self) -> BoxFuture<'_, Result<u64>>;
+
+    /// delete data even when the model is soft deleted
+    fn hard_delete<'r>(&'r mut self, filter: &'r Filter) -> BoxFuture<'r, Result<u64>>;
+
+    /// count the data
+    fn count<'r>(
+        &'r mut self,
+        filter: &'r Filter,
+        other: Option<Other<'r>>,
+    ) -> BoxFuture<'r, Result<i64>>;
+
+    /// query the data by the keys of an entity
+    fn find_by_id<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<T>>;
+
+    /// whether data with the keys of an entity exists
+    fn exists_by_id<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<bool>>;
+
+    /// update the data by the keys of an entity
+    fn update_by_id<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<u64>>;
+
+    /// delete the data by the keys of an entity
+    fn delete_by_id<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<u64>>;
+
+    /// insert an entity when its key values are unset, otherwise update it by id
+    fn save<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<u64>>;
+
+    /// query a piece of data in the database, ordered when the order is not empty
+    /// !!please note that there is an injection risk when using upload fields
+    fn query_one<'r>(
+        &'r mut self,
+        order: &'r str,
+        filter: &'r Filter,
+        other: Option<Other<'r>>,
+    ) -> BoxFuture<'r, Result<T>>;
+
+    /// query some data in the database, paginated by (limit, offset) when given
+    /// !!please note that there is an injection risk when using upload fields
+    fn query<'r>(
+        &'r mut self,
+        order: &'r str,
+        limit: Option<(i64, i64)>,
+        filter: &'r Filter,
+        other: Option<Other<'r>>,
+    ) -> BoxFuture<'r, Result<Vec<T>>>;
+}
+
+impl<T, B> DynExecutor<T> for Repo<T, B>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync + 'static,
+    for<'a> &'a T: Not<Output = (&'static str, &'static [&'static str])>,
+    B: Backend<T>,
+{
+    fn insert_one<'r>(
+        &'r mut self,
+        entity: &'r T,
+        filter: Option<&'r Filter>,
+    ) -> BoxFuture<'r, Result<u64>> {
+        Box::pin(Repo::insert_one(self, entity, filter))
+    }
+
+    fn insert<'r>(&'r mut self, data: &'r [T]) -> BoxFuture<'r, Result<u64>> {
+        Box::pin(Repo::insert(self, data))
+    }
+
+    fn update<'r>(&'r mut self, entity: &'r T, filter: &'r Filter) -> BoxFuture<'r, Result<u64>> {
+        Box::pin(Repo::update(self, entity, filter))
+    }
+
+    fn update_returning<'r>(
+        &'r mut self,
+        entity: &'r T,
+        filter: &'r Filter,
+    ) -> BoxFuture<'r, Result<Vec<T>>> {
+        Box::pin(Repo::update_returning(self, entity, filter))
+    }
+
+    fn update_all<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<u64>> {
+        Box::pin(Repo::update_all(self, entity))
+    }
+
+    fn update_many<'r>(
+        &'r mut self,
+        data: &'r [T],
+        keys: &'r [&'r str],
+    ) -> BoxFuture<'r, Result<Vec<u64>>> {
+        Box::pin(Repo::update_many(self, data, keys))
+    }
+
+    fn delete<'r>(&'r mut self, filter: &'r Filter) -> BoxFuture<'r, Result<u64>> {
+        Box::pin(Repo::delete(self, filter))
+    }
+
+    fn delete_returning<'r>(&'r mut self, filter: &'r Filter) -> BoxFuture<'r, Result<Vec<T>>> {
+        Box::pin(Repo::delete_returning(self, filter))
+    }
+
+    fn delete_all(&mut self) -> BoxFuture<'_, Result<u64>> {
+        Box::pin(Repo::delete_all(self))
+    }
+
+    fn hard_delete<'r>(&'r mut self, filter: &'r Filter) -> BoxFuture<'r, Result<u64>> {
+        Box::pin(Repo::hard_delete(self, filter))
+    }
+
+    fn count<'r>(
+        &'r mut self,
+        filter: &'r Filter,
+        other: Option<Other<'r>>,
+    ) -> BoxFuture<'r, Result<i64>> {
+        Box::pin(Repo::count(self, filter, other))
+    }
+
+    fn find_by_id<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<T>> {
+        Box::pin(Repo::find_by_id(self, entity))
+    }
+
+    fn exists_by_id<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<bool>> {
+        Box::pin(Repo::exists_by_id(self, entity))
+    }
+
+    fn update_by_id<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<u64>> {
+        Box::pin(Repo::update_by_id(self, entity))
+    }
+
+    fn delete_by_id<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<u64>> {
+        Box::pin(Repo::delete_by_id(self, entity))
+    }
+
+    fn save<'r>(&'r mut self, entity: &'r T) -> BoxFuture<'r, Result<u64>> {
+        Box::pin(Repo::save(self, entity))
+    }
+
+    fn query_one<'r>(
+        &'r mut self,
+        order: &'r str,
+        filter: &'r Filter,
+        other: Option<Other<'r>>,
+    ) -> BoxFuture<'r, Result<T>> {
+        Box::pin(self.order_by(order).query_one(filter, other))
+    }
+
+    fn query<'r>(
+        &'r mut self,
+        order: &'r str,
+        limit: Option<(i64, i64)>,
+        filter: &'r Filter,
+        other: Option<Other<'r>>,
+    ) -> BoxFuture<'r, Result<Vec<T>>> {
+        let query = self.order_by(order);
+        match limit {
+            Some((limit, offset)) => Box::pin(query.limit(limit, offset).query(filter, other)),
+            None => Box::pin(query.query(filter, other)),
+        }
+    }
+}
@@ -1,15 +1,18 @@
 #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 #[macro_export]
 macro_rules! sqlx_to_arg {
-    ($value:ident, $query:ident, $($typ:ty),* $(,)?) => {{
+    ($value:ident, $query:ident, $($typ:ty),* $(,)? $(; masked $($mtyp:ty),+ $(,)?)?) => {{
         if let Some(p) = $value.downcast_ref::<Vec<u8>>() {
             $query.push_bind(p);
             Ok(format!("{:?}",p))
         } $(else if let Some(p) = $value.downcast_ref::<$typ>() {
             $query.push_bind(p);
             Ok(p.to_string())
-        })* else {
-            Err($crate::anyhow!("arguments after `{}` cannot be converted please customize the function to_arg",$query.sql()))
+        })* $($(else if let Some(p) = $value.downcast_ref::<$mtyp>() {
+            $query.push_bind(p);
+            Ok($crate::model::MASK.to_string())
+        })+)? else {
+            Err($crate::Error::Conversion { field: None, ty: None, source: None, sql: None })
         }
     }};
 }
@@ -19,11 +22,11 @@ macro_rules! sqlx_to_arg {
 macro_rules! sqlx_from_row {
     ($name:ident, $row:ident, $value:ident, $($typ:ty),* $(,)?) => {{
         if let Some(p) = $value.downcast_mut::<Vec<u8>>() {
-            *p = sqlx::Row::try_get($row,$name)?;
+            *p = sqlx::Row::try_get($row,$name).map_err(|err| $crate::Error::conversion($name, "Vec<u8>", err))?;
         } $(else if let Some(p) = $value.downcast_mut::<$typ>() {
-            *p = sqlx::Row::try_get($row,$name)?;
+            *p = sqlx::Row::try_get($row,$name).map_err(|err| $crate::Error::conversion($name, stringify!($typ), err))?;
         })* else{
-            return Err($crate::anyhow!("`{}` cannot be converted please customize the function from_row",$name));
+            return Err($crate::Error::Conversion { field: Some($name.to_string()), ty: None, source: None, sql: None });
         }
         Ok(())
     }};
@@ -32,15 +35,18 @@ macro_rules! sqlx_from_row {
 #[cfg(feature = "mssql")]
 #[macro_export]
 macro_rules! mssql_to_arg {
-    ($value:ident, $args:ident, $($typ:ty),* $(,)?) => {{
+    ($value:ident, $args:ident, $($typ:ty),* $(,)? $(; masked $($mtyp:ty),+ $(,)?)?) => {{
         if let Some(p) = $value.downcast_ref::<Vec<u8>>() {
             $args.push(p);
             Ok(format!("{:?}",p))
         } $(else if let Some(p) = $value.downcast_ref::<$typ>() {
             $args.push(p);
             Ok(p.to_string())
-        })* else {
-            Err($crate::anyhow!("arguments at index {} cannot be converted please customize the function to_arg",$args.len()))
+        })* $($(else if let Some(p) = $value.downcast_ref::<$mtyp>() {
+            $args.push(p);
+            Ok($crate::model::MASK.to_string())
+        })+)? else {
+            Err($crate::Error::Conversion { field: None, ty: None, source: None, sql: None })
         }
     }};
 }
@@ -50,76 +56,435 @@ macro_rules! mssql_to_arg {
 macro_rules! mssql_from_row {
     ($name:ident, $row:ident, $value:ident, $($typ:ty),* $(,)?) => {{
         if let Some(p) = $value.downcast_mut::<String>() {
-            if let Some(t) = $row.try_get::<'_,&str,_>($name)? {
+            if let Some(t) = $row.try_get::<'_,&str,_>($name).map_err(|err| $crate::Error::conversion($name, "String", err))? {
                 *p = t.to_string();
             }
         } else if let Some(p) = $value.downcast_mut::<Vec<u8>>() {
-            if let Some(t) = $row.try_get::<'_,&[u8],_>($name)? {
+            if let Some(t) = $row.try_get::<'_,&[u8],_>($name).map_err(|err| $crate::Error::conversion($name, "Vec<u8>", err))? {
                 *p = t.to_vec();
             }
         } $(else if let Some(p) = $value.downcast_mut::<$typ>() {
-            if let Some(t) = $row.try_get($name)? {
+            if let Some(t) = $row.try_get($name).map_err(|err| $crate::Error::conversion($name, stringify!($typ), err))? {
                 *p = t;
             }
         })* else{
-            return Err($crate::anyhow!("`{}` cannot be converted please customize the function from_row",$name));
+            return Err($crate::Error::Conversion { field: Some($name.to_string()), ty: None, source: None, sql: None });
         }
         Ok(())
     }};
 }
 
 #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
-macro_rules! sqlx_insert_one {
-    ($my:ident, $filter:ident) => {{
-        let (_, fnames) = !$my.model.entity;
+macro_rules! sqlx_push_value {
+    ($my:ident, $builder:ident, $args:ident, $value:expr, $field:expr, $stamp:expr) => {{
+        //automatic timestamps replace the entity value
+        match $stamp {
+            $crate::model::Stamp::Clock($crate::model::Clock::Database) => {
+                $builder.push(NOW);
+            }
+            $crate::model::Stamp::Clock($crate::model::Clock::Value(p)) => {
+                let arg = ($my.to_arg)(p, &mut $builder)
+                    .map_err(|err| $crate::model::arg_error($builder.sql(), err))?;
+                $args.push_str(&$crate::model::shown(arg, p, false));
+                $args.push(' ');
+            }
+            _ => {
+                let arg = ($my.to_arg)($value, &mut $builder)
+                    .map_err(|err| $crate::model::arg_error($builder.sql(), err))?;
+                $args.push_str(&$crate::model::shown(arg, $value, $field));
+                $args.push(' ');
+            }
+        }
+    }};
+}
 
-        let mut builder = QueryBuilder::new("insert into ");
-        builder.push($my.model.table);
+#[cfg(feature = "mssql")]
+macro_rules! mssql_push_value {
+    ($my:ident, $query:ident, $params:ident, $args:ident, $value:expr, $field:expr, $stamp:expr) => {{
+        //automatic timestamps replace the entity value
+        match $stamp {
+            $crate::model::Stamp::Clock($crate::model::Clock::Database) => {
+                $query.push_str("getdate()");
+            }
+            $crate::model::Stamp::Clock($crate::model::Clock::Value(p)) => {
+                let arg = ($my.to_arg)(p, &mut $params)
+                    .map_err(|err| $crate::model::arg_error(&$query, err))?;
+                $args.push_str(&$crate::model::shown(arg, p, false));
+                $args.push(' ');
+                $query.push_str("@P");
+                $query.push_str(&$params.len().to_string());
+            }
+            _ => {
+                let arg = ($my.to_arg)($value, &mut $params)
+                    .map_err(|err| $crate::model::arg_error(&$query, err))?;
+                $args.push_str(&$crate::model::shown(arg, $value, $field));
+                $args.push(' ');
+                $query.push_str("@P");
+                $query.push_str(&$params.len().to_string());
+            }
+        }
+    }};
+}
 
-        //fields section
-        let mut sep = false;
-        builder.push("(");
-        for fd in fnames {
-            if let Some(&co) = $my.model.fields.get(fd) {
-                if co.len() > 0 {
-                    if co != "-" {
-                        if sep {
-                            builder.push(",");
-                        }
-                        builder.push(co);
-                        sep = true;
-                    }
+#[cfg(feature = "mssql")]
+macro_rules! mssql_push_conditions {
+    ($my:ident, $query:ident, $params:ident, $args:ident, $scope:expr, $conds:expr, $and:expr) => {{
+        //soft delete, key and version conditions section
+        let mut and = $and;
+        for sc in $scope.iter() {
+            $query.push_str(if and { " and " } else { " where " });
+            $query.push_str(sc);
+            and = true;
+        }
+        for (ix, co) in $conds.iter() {
+            $query.push_str(if and { " and " } else { " where " });
+            $query.push_str(&$crate::model::quote_table(&$my.model.table, QUOTE));
+            $query.push('.');
+            $query.push_str(&$crate::model::quote(co, QUOTE));
+            $query.push('=');
+            let arg = ($my.to_arg)(&$my.model.entity[*ix], &mut $params)
+                .map_err(|err| $crate::model::arg_error(&$query, err))?;
+            $args.push_str(&$crate::model::shown(
+                arg,
+                &$my.model.entity[*ix],
+                $my.model.redacted(*ix),
+            ));
+            $args.push(' ');
+            $query.push_str("@P");
+            $query.push_str(&$params.len().to_string());
+            and = true;
+        }
+    }};
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+macro_rules! sqlx_push_conditions {
+    ($my:ident, $builder:ident, $args:ident, $scope:expr, $conds:expr, $and:expr) => {{
+        //soft delete, key and version conditions section
+        let mut and = $and;
+        for sc in $scope.iter() {
+            $builder.push(if and { " and " } else { " where " });
+            $builder.push(sc);
+            and = true;
+        }
+        for (ix, co) in $conds.iter() {
+            $builder.push(if and { " and " } else { " where " });
+            $builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+            $builder.push(".");
+            $builder.push($crate::model::quote(co, QUOTE));
+            $builder.push("=");
+            let arg = ($my.to_arg)(&$my.model.entity[*ix], &mut $builder)
+                .map_err(|err| $crate::model::arg_error($builder.sql(), err))?;
+            $args.push_str(&$crate::model::shown(
+                arg,
+                &$my.model.entity[*ix],
+                $my.model.redacted(*ix),
+            ));
+            $args.push(' ');
+            and = true;
+        }
+    }};
+}
+
+/// run a round trip of a statement before its deadline, the statement fails with
+/// `Timeout` when the deadline passes, a dropped round trip that leaves the
+/// connection unusable is marked with `discard`
+macro_rules! timed {
+    ($deadline:expr, $trace:ident, $future:expr, $sql:expr) => {
+        match $deadline.run($trace.instrument($future)).await {
+            Some(res) => res,
+            None => return Err($crate::Error::Timeout($crate::Timeout::new($sql))),
+        }
+    };
+    ($deadline:expr, $trace:ident, $future:expr, $sql:expr, discard) => {
+        match $deadline.run($trace.instrument($future)).await {
+            Some(res) => res,
+            None => {
+                return Err($crate::Error::Timeout(
+                    $crate::Timeout::new($sql).discarded(),
+                ))
+            }
+        }
+    };
+}
+
+/// run a statement again on a retryable error while the retry policy allows it
+#[cfg(feature = "mssql")]
+macro_rules! retried {
+    ($retry:expr, $run:expr) => {{
+        let mut attempts = $crate::model::Attempts::new($retry);
+        loop {
+            let res = $run.await;
+            match attempts.next(&res) {
+                Some(delay) => $crate::model::sleep(delay).await,
+                None => break res,
+            }
+        }
+    }};
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+macro_rules! sqlx_expect {
+    ($my:ident, $run:ident, $($arg:tt)*) => {{
+        //an expected row count runs the write in a transaction, nested ones are savepoints
+        match $my.model.expected() {
+            None => $run!($my, $($arg)*),
+            Some(expect) => {
+                let mut tx = $crate::model::Bound::transaction($my.executor).await?;
+                let table = $my.model.table.clone();
+                let my = $crate::model::TxModel {
+                    model: $my.model,
+                    executor: &mut *tx,
+                    to_arg: $my.to_arg,
+                    from_row: $my.from_row,
+                    order: $my.order,
+                    limit: $my.limit,
+                    offset: $my.offset,
+                };
+                let res = $run!(my, $($arg)*);
+                let rows = $crate::model::Affected::affected(&res);
+                if let Err(err) = $crate::model::check_rows(&table, Some(expect), rows) {
+                    tx.rollback().await?;
+                    return Err(err);
+                }
+                tx.commit().await?;
+                res
+            }
+        }
+    }};
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+macro_rules! sqlx_retry {
+    ($my:ident, $write:expr, $run:ident, $($arg:tt)*) => {{
+        //a retried statement runs again on one connection, not when it is in a transaction
+        //or its executor can not be acquired
+        match $my.model.retry_of($write) {
+            Some(retry) if $crate::model::Bound::acquirable(&$my.executor) => {
+                let mut conn = $crate::model::Bound::connection($my.executor).await?;
+                let retry = if sqlx::Connection::is_in_transaction(&*conn) {
+                    None
                 } else {
-                    if sep {
-                        builder.push(",");
+                    Some(retry)
+                };
+                let model = $my.model;
+                let mut attempts = $crate::model::Attempts::new(retry);
+                loop {
+                    let my = $crate::model::TxModel {
+                        model: model.clone(),
+                        executor: &mut *conn,
+                        to_arg: &$my.to_arg,
+                        from_row: &$my.from_row,
+                        order: $my.order,
+                        limit: $my.limit,
+                        offset: $my.offset,
+                    };
+                    let res: $crate::Result<_> = async { Ok($run!(my, $($arg)*)) }.await;
+                    match attempts.next(&res) {
+                        Some(delay) => $crate::model::sleep(delay).await,
+                        None => break res?,
+                    }
+                }
+            }
+            _ => $run!($my, $($arg)*),
+        }
+    }};
+}
+
+#[cfg(feature = "postgres")]
+macro_rules! pg_timeout {
+    ($my:ident, $run:ident, $($arg:tt)*) => {{
+        //the server side limit of postgres is set on a transaction of the statement,
+        //the previous limit is put back as a savepoint does not end it, an executor that
+        //can not be acquired keeps the client side limit
+        match $my.model.timeout {
+            Some(timeout) if $crate::model::Bound::acquirable(&$my.executor) => {
+                let mut model = $my.model;
+                model.timeout = None;
+                let mut tx = $crate::model::Bound::transaction($my.executor).await?;
+                let previous: String = sqlx::query_scalar("select current_setting('statement_timeout')")
+                    .fetch_one(&mut *tx)
+                    .await?;
+                sqlx::query("select set_config('statement_timeout', $1, true)")
+                    .bind($crate::model::millis(timeout).to_string())
+                    .execute(&mut *tx)
+                    .await?;
+                let my = $crate::model::TxModel {
+                    model,
+                    executor: &mut *tx,
+                    to_arg: $my.to_arg,
+                    from_row: $my.from_row,
+                    order: $my.order,
+                    limit: $my.limit,
+                    offset: $my.offset,
+                };
+                let res = $run!(my, $($arg)*);
+                sqlx::query("select set_config('statement_timeout', $1, true)")
+                    .bind(previous)
+                    .execute(&mut *tx)
+                    .await?;
+                tx.commit().await?;
+                res
+            }
+            _ => $run!($my, $($arg)*),
+        }
+    }};
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+macro_rules! sqlx_transact {
+    ($pool:ty, $conn:ty, $db:ty) => {
+        impl $crate::model::Transact for $pool {
+            type Tx<'t> = sqlx::Transaction<'t, $db>;
+
+            fn begin(
+                &mut self,
+                isolation: Option<$crate::model::Isolation>,
+            ) -> $crate::model::BoxFuture<'_, Result<Self::Tx<'_>>> {
+                Box::pin(async move {
+                    let mut conn = self.acquire().await?;
+                    let statement = begin_statement(&mut conn, isolation).await?;
+                    Ok(sqlx::Transaction::begin(conn, statement).await?)
+                })
+            }
+
+            fn commit(tx: Self::Tx<'_>) -> $crate::model::BoxFuture<'_, Result<()>> {
+                Box::pin(async move { Ok(tx.commit().await?) })
+            }
+
+            fn rollback(tx: Self::Tx<'_>) -> $crate::model::BoxFuture<'_, Result<()>> {
+                Box::pin(async move { Ok(tx.rollback().await?) })
+            }
+        }
+
+        impl $crate::model::Transact for $conn {
+            type Tx<'t> = sqlx::Transaction<'t, $db>;
+
+            fn begin(
+                &mut self,
+                isolation: Option<$crate::model::Isolation>,
+            ) -> $crate::model::BoxFuture<'_, Result<Self::Tx<'_>>> {
+                Box::pin(async move {
+                    //savepoints keep the isolation level of their transaction
+                    if sqlx::Connection::is_in_transaction(self) {
+                        return Ok(sqlx::Transaction::begin(self, None).await?);
+                    }
+                    let statement = begin_statement(self, isolation).await?;
+                    Ok(sqlx::Transaction::begin(self, statement).await?)
+                })
+            }
+
+            fn commit(tx: Self::Tx<'_>) -> $crate::model::BoxFuture<'_, Result<()>> {
+                Box::pin(async move { Ok(tx.commit().await?) })
+            }
+
+            fn rollback(tx: Self::Tx<'_>) -> $crate::model::BoxFuture<'_, Result<()>> {
+                Box::pin(async move { Ok(tx.rollback().await?) })
+            }
+        }
+    };
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+macro_rules! sqlx_execute {
+    ($my:ident, $builder:ident, $args:ident, $kind:expr, $soft:expr) => {{
+        let mut sql = $builder.sql().to_string();
+        let arguments = $crate::model::arguments($builder.build())?;
+        let (trace, binds) = $my.model.trace(
+            $kind,
+            $soft,
+            &mut sql,
+            &$args,
+            sqlx::Arguments::len(&arguments),
+        )?;
+        let res = match timed!(
+            $my.model.deadline(),
+            trace,
+            $crate::model::statement(&sql, arguments, binds)?
+                .execute($crate::model::Bound::executor($my.executor)),
+            &sql
+        ) {
+            Ok(res) => res.rows_affected(),
+            Err(err) => return Err(trace.fail($crate::model::sql_error(&sql, $args, err))),
+        };
+        trace.rows(res);
+        (res, res)
+    }};
+}
+
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+macro_rules! sqlx_returning {
+    ($my:ident, $builder:ident, $args:ident, $kind:expr, $soft:expr) => {{
+        //returning statement section
+        $builder.push(" returning ");
+        $builder.push($my.model.returning_list(QUOTE));
+
+        //query column section
+        let fds = $my.model.returned_fields();
+
+        let mut res = Vec::new();
+        let mut sql = $builder.sql().to_string();
+        let arguments = $crate::model::arguments($builder.build())?;
+        let (trace, binds) = $my.model.trace(
+            $kind,
+            $soft,
+            &mut sql,
+            &$args,
+            sqlx::Arguments::len(&arguments),
+        )?;
+        let deadline = $my.model.deadline();
+        let mut stream = $crate::model::statement(&sql, arguments, binds)?
+            .fetch($crate::model::Bound::executor($my.executor));
+        while let Some(rst) = timed!(deadline, trace, stream.next(), &sql) {
+            match rst {
+                Ok(row) => {
+                    let mut entity = $my.model.entity.clone();
+                    for (ix, fd) in fds.iter() {
+                        ($my.from_row)(fd, &row, &mut entity[*ix])?;
                     }
-                    builder.push(fd);
-                    sep = true;
+                    res.push(entity);
                 }
+                Err(err) => return Err(trace.fail($crate::model::sql_error(&sql, $args, err))),
             }
         }
+        trace.rows(res.len() as u64);
+        let rows = res.len() as u64;
+        (res, rows)
+    }};
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+macro_rules! sqlx_insert_one {
+    ($my:ident, $filter:ident) => {{
+        let mut builder = QueryBuilder::new("insert into ");
+        builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+
+        //fields section
+        builder.push("(");
+        builder.push($my.model.insert_list(QUOTE));
         builder.push(")");
 
         let mut args = " ".to_string();
 
         //values section
-        sep = false;
+        let mut sep = false;
         if $filter.is_some() {
             builder.push(" select ");
         } else {
             builder.push(" values (");
         }
-        for (ix, fd) in fnames.iter().enumerate() {
-            if let Some(&co) = $my.model.fields.get(fd) {
-                if co == "-" {
-                    continue;
-                }
-            }
+        for &(ix, fd) in $my.model.insert_fields().iter() {
             if sep {
                 builder.push(",");
             }
-            args.push_str(&($my.to_arg)(&$my.model.entity[ix], &mut builder)?);
-            args.push(' ');
+            sqlx_push_value!(
+                $my,
+                builder,
+                args,
+                &$my.model.entity[ix],
+                $my.model.redacted(ix),
+                $my.model.stamp(fd, true)
+            );
             sep = true;
         }
 
@@ -129,15 +494,18 @@ macro_rules! sqlx_insert_one {
 
             let mut idx = 0;
             let mut quo = false;
-            for ch in flt.expr.chars() {
+            for ch in $crate::model::quote_expr(&flt.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= flt.args.len() {
-                        return Err($crate::anyhow!(" ? exceeds the number of args"));
+                        return Err($crate::Error::ArgCountMismatch);
                     }
-                    args.push_str(&($my.to_arg)(&*flt.args[idx], &mut builder)?);
+                    let field = $my.model.redacted_filter(builder.sql());
+                    let arg = ($my.to_arg)(&*flt.args[idx], &mut builder)
+                        .map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                    args.push_str(&$crate::model::shown(arg, &*flt.args[idx], field));
                     args.push(' ');
                     idx += 1;
                 } else {
@@ -147,26 +515,33 @@ macro_rules! sqlx_insert_one {
             debug_assert!(!quo, "where statement quotation mark not closed error");
 
             if idx != flt.args.len() {
-                return Err($crate::anyhow!(
-                    "the quantity of ? is not equal to the number of parameters"
-                ));
+                return Err($crate::Error::ArgCountMismatch);
             }
         } else {
             builder.push(")");
         }
 
         //execute sql statements
-        let res = match builder.build().execute($my.executor).await {
+        let mut sql = builder.sql().to_string();
+        let arguments = $crate::model::arguments(builder.build())?;
+        let (trace, binds) = $my.model.trace(
+            $crate::model::Kind::Insert,
+            false,
+            &mut sql,
+            &args,
+            sqlx::Arguments::len(&arguments),
+        )?;
+        let res = match timed!(
+            $my.model.deadline(),
+            trace,
+            $crate::model::statement(&sql, arguments, binds)?
+                .execute($crate::model::Bound::executor($my.executor)),
+            &sql
+        ) {
             Ok(res) => res.rows_affected(),
-            Err(err) => {
-                return Err($crate::anyhow!(
-                    "sql:`{}` args:[{}]  {}",
-                    builder.sql(),
-                    args,
-                    err
-                ))
-            }
+            Err(err) => return Err(trace.fail($crate::model::sql_error(&sql, args, err))),
         };
+        trace.rows(res);
 
         res
     }};
@@ -175,39 +550,18 @@ macro_rules! sqlx_insert_one {
 #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 macro_rules! sqlx_insert {
     ($my:ident, $data:ident) => {{
-        let (_, fnames) = !$my.model.entity;
-
         let mut builder = QueryBuilder::new("insert into ");
-        builder.push($my.model.table);
+        builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
 
         //fields section
-        let mut sep = false;
         builder.push("(");
-        for fd in fnames {
-            if let Some(&co) = $my.model.fields.get(fd) {
-                if co.len() > 0 {
-                    if co != "-" {
-                        if sep {
-                            builder.push(",");
-                        }
-                        builder.push(co);
-                        sep = true;
-                    }
-                } else {
-                    if sep {
-                        builder.push(",");
-                    }
-                    builder.push(fd);
-                    sep = true;
-                }
-            }
-        }
+        builder.push($my.model.insert_list(QUOTE));
         builder.push(") values ");
 
         let mut args = String::new();
 
         //values section
-        sep = false;
+        let mut sep = false;
         for row in $data {
             if sep {
                 builder.push(",");
@@ -216,17 +570,18 @@ macro_rules! sqlx_insert {
             builder.push("(");
             args.push_str("[ ");
             let mut sp = false;
-            for (ix, fd) in fnames.iter().enumerate() {
-                if let Some(&co) = $my.model.fields.get(fd) {
-                    if co == "-" {
-                        continue;
-                    }
-                }
+            for &(ix, fd) in $my.model.insert_fields().iter() {
                 if sp {
                     builder.push(",");
                 }
-                args.push_str(&($my.to_arg)(&row[ix], &mut builder)?);
-                args.push(' ');
+                sqlx_push_value!(
+                    $my,
+                    builder,
+                    args,
+                    &row[ix],
+                    $my.model.redacted(ix),
+                    $my.model.stamp(fd, true)
+                );
                 sp = true;
             }
             builder.push(")");
@@ -235,17 +590,139 @@ macro_rules! sqlx_insert {
         }
 
         //execute sql statements
-        let res = match builder.build().execute($my.executor).await {
+        let mut sql = builder.sql().to_string();
+        let arguments = $crate::model::arguments(builder.build())?;
+        let (trace, binds) = $my.model.trace(
+            $crate::model::Kind::Insert,
+            false,
+            &mut sql,
+            &args,
+            sqlx::Arguments::len(&arguments),
+        )?;
+        let res = match timed!(
+            $my.model.deadline(),
+            trace,
+            $crate::model::statement(&sql, arguments, binds)?
+                .execute($crate::model::Bound::executor($my.executor)),
+            &sql
+        ) {
             Ok(res) => res.rows_affected(),
-            Err(err) => {
-                return Err($crate::anyhow!(
-                    "sql:`{}` args:[{}]  {}",
-                    builder.sql(),
+            Err(err) => return Err(trace.fail($crate::model::sql_error(&sql, args, err))),
+        };
+        trace.rows(res);
+
+        res
+    }};
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+macro_rules! sqlx_insert_select {
+    ($my:ident, $source:ident, $filter:ident) => {{
+        let mut builder = QueryBuilder::new("insert into ");
+        builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+
+        //fields section
+        builder.push("(");
+        builder.push($my.model.insert_list(QUOTE));
+        builder.push(")");
+
+        let mut args = " ".to_string();
+
+        //select section
+        let mut sep = false;
+        builder.push(" select ");
+        for &(ix, fd) in $my.model.insert_fields().iter() {
+            if sep {
+                builder.push(",");
+            }
+            match $my.model.stamp(fd, true) {
+                $crate::model::Stamp::Entity => {
+                    builder.push($crate::model::quote($source.column(fd)?, QUOTE));
+                }
+                stamp => sqlx_push_value!(
+                    $my,
+                    builder,
                     args,
-                    err
-                ))
+                    &$my.model.entity[ix],
+                    $my.model.redacted(ix),
+                    stamp
+                ),
+            }
+            sep = true;
+        }
+        builder.push(" from ");
+        builder.push($crate::model::quote_table(&$source.table, QUOTE));
+
+        let mut idx = 0;
+
+        //where statement section
+        if !$filter.expr.is_empty() {
+            builder.push(" where ");
+            if $source.scope.is_some() {
+                builder.push("(");
+            }
+
+            let mut quo = false;
+            for ch in $crate::model::quote_expr(&$filter.expr, QUOTE).chars() {
+                if ch == '\'' {
+                    quo = !quo;
+                }
+                if ch == '?' && !quo {
+                    if idx >= $filter.args.len() {
+                        return Err($crate::Error::ArgCountMismatch);
+                    }
+                    let field = $my.model.redacted_filter(builder.sql());
+                    let arg = ($my.to_arg)(&*$filter.args[idx], &mut builder)
+                        .map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                    args.push_str(&$crate::model::shown(arg, &*$filter.args[idx], field));
+                    args.push(' ');
+                    idx += 1;
+                } else {
+                    builder.push(ch);
+                }
+            }
+            debug_assert!(!quo, "where statement quotation mark not closed error");
+            if $source.scope.is_some() {
+                builder.push(")");
             }
+        }
+        if let Some((column, predicate)) = &$source.scope {
+            builder.push(if $filter.expr.is_empty() {
+                " where "
+            } else {
+                " and "
+            });
+            builder.push($crate::model::quote_table(&$source.table, QUOTE));
+            builder.push(".");
+            builder.push($crate::model::quote(column, QUOTE));
+            builder.push(predicate);
+        }
+
+        if idx != $filter.args.len() {
+            return Err($crate::Error::ArgCountMismatch);
+        }
+
+        //execute sql statements
+        let mut sql = builder.sql().to_string();
+        let arguments = $crate::model::arguments(builder.build())?;
+        let (trace, binds) = $my.model.trace(
+            $crate::model::Kind::Insert,
+            false,
+            &mut sql,
+            &args,
+            sqlx::Arguments::len(&arguments),
+        )?;
+        let res = match timed!(
+            $my.model.deadline(),
+            trace,
+            $crate::model::statement(&sql, arguments, binds)?
+                .execute($crate::model::Bound::executor($my.executor)),
+            &sql
+        ) {
+            Ok(res) => res.rows_affected(),
+            Err(err) => return Err(trace.fail($crate::model::sql_error(&sql, args, err))),
         };
+        trace.rows(res);
 
         res
     }};
@@ -253,11 +730,16 @@ macro_rules! sqlx_insert {
 
 #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 macro_rules! sqlx_update {
-    ($my:ident, $filter:ident) => {{
+    ($my:ident, $filter:ident) => {
+        sqlx_update!($my, $filter, sqlx_execute)
+    };
+    ($my:ident, $filter:ident, $run:ident) => {{
         let (_, fnames) = !$my.model.entity;
+        let version = $my.model.version_column()?;
+        let ids = $my.model.id_columns()?;
 
         let mut builder = QueryBuilder::new("update ");
-        builder.push($my.model.table);
+        builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
         builder.push(" set ");
 
         let mut args = " ".to_string();
@@ -265,47 +747,59 @@ macro_rules! sqlx_update {
         //update statement section
         let mut sep = false;
         for (ix, fd) in fnames.iter().enumerate() {
-            if let Some(&co) = $my.model.fields.get(fd) {
-                if co.len() > 0 {
-                    if co != "-" {
-                        if sep {
-                            builder.push(",");
-                        }
-                        builder.push(co);
-                        builder.push("=");
-                        args.push_str(&($my.to_arg)(&$my.model.entity[ix], &mut builder)?);
-                        args.push(' ');
-                        sep = true;
-                    }
-                } else {
-                    if sep {
-                        builder.push(",");
-                    }
-                    builder.push(fd);
-                    builder.push("=");
-                    args.push_str(&($my.to_arg)(&$my.model.entity[ix], &mut builder)?);
-                    args.push(' ');
-                    sep = true;
-                }
+            if !$my.model.updatable(fd) {
+                continue;
+            }
+            let stamp = $my.model.stamp(fd, false);
+            if matches!(version, Some((vx, _)) if vx == ix)
+                || matches!(stamp, $crate::model::Stamp::Skip)
+                || ids.iter().any(|(kx, _)| *kx == ix)
+            {
+                continue;
+            }
+            if sep {
+                builder.push(",");
+            }
+            builder.push($crate::model::quote($my.model.column(fd), QUOTE));
+            builder.push("=");
+            sqlx_push_value!($my, builder, args, &$my.model.entity[ix],$my.model.redacted(ix), stamp);
+            sep = true;
+        }
+
+        //version statement section
+        if let Some((_, vc)) = version {
+            if sep {
+                builder.push(",");
             }
+            builder.push($crate::model::quote(vc, QUOTE));
+            builder.push("=");
+            builder.push($crate::model::quote(vc, QUOTE));
+            builder.push("+1");
         }
 
         let mut idx = 0;
 
         //where statement section
+        let conds = ids.iter().chain(version.iter()).collect::<Vec<_>>();
         if $filter.expr.len() > 0 {
             builder.push(" where ");
+            if !conds.is_empty() {
+                builder.push("(");
+            }
 
             let mut quo = false;
-            for ch in $filter.expr.chars() {
+            for ch in $crate::model::quote_expr(&$filter.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= $filter.args.len() {
-                        return Err($crate::anyhow!(" ? exceeds the number of args"));
+                        return Err($crate::Error::ArgCountMismatch);
                     }
-                    args.push_str(&($my.to_arg)(&*$filter.args[idx], &mut builder)?);
+                    let field = $my.model.redacted_filter(builder.sql());
+                    let arg = ($my.to_arg)(&*$filter.args[idx], &mut builder)
+.map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                    args.push_str(&$crate::model::shown(arg, &*$filter.args[idx], field));
                     args.push(' ');
                     idx += 1;
                 } else {
@@ -313,26 +807,410 @@ macro_rules! sqlx_update {
                 }
             }
             debug_assert!(!quo, "where statement quotation mark not closed error");
+            if !conds.is_empty() {
+                builder.push(")");
+            }
         }
+        sqlx_push_conditions!($my, builder, args, None::<String>, conds, !$filter.expr.is_empty());
 
         if idx != $filter.args.len() {
-            return Err($crate::anyhow!(
-                "the quantity of ? is not equal to the number of parameters"
-            ));
+            return Err($crate::Error::ArgCountMismatch);
+        }
+
+        //execute sql statements
+        let (res, rows) = $run!($my, builder, args, $crate::model::Kind::Update, false);
+
+        if version.is_some() && rows == 0 {
+            return Err($crate::Error::StaleEntity($crate::StaleEntity {
+                table: $my.model.table.clone(),
+            }));
+        }
+
+        res
+    }};
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+macro_rules! sqlx_update_many_rows {
+    ($my:ident, $data:ident, $builder:ident, $args:ident, $cols:ident) => {{
+        //derived table of key, update and version values, columns c0, c1 ...
+        for (n, row) in $data.iter().enumerate() {
+            if n > 0 {
+                $builder.push(" union all ");
+                $args.push(' ');
+            }
+            $builder.push("select ");
+            $args.push_str("[ ");
+            for (m, (ix, _)) in $cols.iter().enumerate() {
+                if m > 0 {
+                    $builder.push(",");
+                }
+                let arg = ($my.to_arg)(&row[*ix], &mut $builder)
+                    .map_err(|err| $crate::model::arg_error($builder.sql(), err))?;
+                $args.push_str(&$crate::model::shown(
+                    arg,
+                    &row[*ix],
+                    $my.model.redacted(*ix),
+                ));
+                $args.push(' ');
+                if n == 0 {
+                    $builder.push(" as c");
+                    $builder.push(m);
+                }
+            }
+            $args.push(']');
+        }
+    }};
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+macro_rules! sqlx_update_many {
+    ($my:ident, $data:ident, $keys:ident, $batch:ident) => {{
+        let mut counts = Vec::new();
+        if !$data.is_empty() {
+            let (kcols, vcols) = $my.model.update_columns($keys)?;
+            let version = $my.model.version_column()?;
+            let cols = kcols
+                .iter()
+                .chain(vcols.iter())
+                .chain(version.iter())
+                .collect::<Vec<_>>();
+
+            //data over the parameter limit is split into batches run in a transaction
+            let size = $crate::model::batch_rows(PARAMS, cols.len());
+            if $data.len() <= size {
+                counts.push($batch!(
+                    $my,
+                    $crate::model::Bound::executor($my.executor),
+                    $data,
+                    kcols,
+                    vcols,
+                    version,
+                    cols
+                ));
+            } else {
+                let mut tx = $crate::model::Bound::transaction($my.executor).await?;
+                for rows in $data.chunks(size) {
+                    counts.push($batch!($my, &mut *tx, rows, kcols, vcols, version, cols));
+                }
+                tx.commit().await?;
+            }
+        }
+        $crate::model::Batches(counts)
+    }};
+}
+
+#[cfg(feature = "sqlite")]
+macro_rules! sqlx_update_many_each {
+    ($my:ident, $data:ident, $keys:ident) => {{
+        let mut counts = Vec::new();
+        if !$data.is_empty() {
+            let (kcols, vcols) = $my.model.update_columns($keys)?;
+            let version = $my.model.version_column()?;
+            let size = $crate::model::batch_rows(
+                PARAMS,
+                kcols.len() + vcols.len() + version.iter().count(),
+            );
+
+            //the rows are updated one by one in a transaction, counted by batch
+            let mut tx = $crate::model::Bound::transaction($my.executor).await?;
+            for rows in $data.chunks(size) {
+                let mut count = 0;
+                for row in rows {
+                    let mut builder = QueryBuilder::new("update ");
+                    builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+                    builder.push(" set ");
+                    let mut args = String::new();
+
+                    //update statement section
+                    for (n, (ix, co)) in vcols.iter().enumerate() {
+                        if n > 0 {
+                            builder.push(",");
+                        }
+                        builder.push($crate::model::quote(co, QUOTE));
+                        builder.push("=");
+                        let arg = ($my.to_arg)(&row[*ix], &mut builder)
+                            .map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                        args.push_str(&$crate::model::shown(
+                            arg,
+                            &row[*ix],
+                            $my.model.redacted(*ix),
+                        ));
+                        args.push(' ');
+                    }
+
+                    //version statement section
+                    if let Some((_, vc)) = version {
+                        builder.push(",");
+                        builder.push($crate::model::quote(vc, QUOTE));
+                        builder.push("=");
+                        builder.push($crate::model::quote(vc, QUOTE));
+                        builder.push("+1");
+                    }
+
+                    //updated time statement section
+                    if let Some((uc, clock)) = $my.model.updated_column() {
+                        builder.push(",");
+                        builder.push($crate::model::quote(uc, QUOTE));
+                        builder.push("=");
+                        sqlx_push_value!(
+                            $my,
+                            builder,
+                            args,
+                            &(),
+                            false,
+                            $crate::model::Stamp::Clock(clock)
+                        );
+                    }
+
+                    //where statement section
+                    builder.push(" where ");
+                    for (n, (ix, co)) in kcols.iter().chain(version.iter()).enumerate() {
+                        if n > 0 {
+                            builder.push(" and ");
+                        }
+                        builder.push($crate::model::quote(co, QUOTE));
+                        builder.push("=");
+                        let arg = ($my.to_arg)(&row[*ix], &mut builder)
+                            .map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                        args.push_str(&$crate::model::shown(
+                            arg,
+                            &row[*ix],
+                            $my.model.redacted(*ix),
+                        ));
+                        args.push(' ');
+                    }
+
+                    //execute sql statements
+                    let mut sql = builder.sql().to_string();
+                    let arguments = $crate::model::arguments(builder.build())?;
+                    let (trace, binds) = $my.model.trace(
+                        $crate::model::Kind::Update,
+                        false,
+                        &mut sql,
+                        &args,
+                        sqlx::Arguments::len(&arguments),
+                    )?;
+                    let res = match timed!(
+                        $my.model.deadline(),
+                        trace,
+                        $crate::model::statement(&sql, arguments, binds)?.execute(&mut *tx),
+                        &sql
+                    ) {
+                        Ok(res) => res.rows_affected(),
+                        Err(err) => {
+                            return Err(trace.fail($crate::model::sql_error(&sql, args, err)))
+                        }
+                    };
+                    trace.rows(res);
+
+                    if version.is_some() && res == 0 {
+                        return Err($crate::Error::StaleEntity($crate::StaleEntity {
+                            table: $my.model.table.clone(),
+                        }));
+                    }
+                    count += res;
+                }
+                counts.push(count);
+            }
+            tx.commit().await?;
+        }
+        $crate::model::Batches(counts)
+    }};
+}
+
+#[cfg(feature = "postgres")]
+macro_rules! sqlx_update_many_from {
+    ($my:ident, $executor:expr, $rows:ident, $kcols:ident, $vcols:ident, $version:ident, $cols:ident) => {{
+        let mut builder = QueryBuilder::new("update ");
+        builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+        builder.push(" set ");
+
+        //update statement section
+        for (n, (_, co)) in $vcols.iter().enumerate() {
+            if n > 0 {
+                builder.push(",");
+            }
+            builder.push($crate::model::quote(co, QUOTE));
+            builder.push("=v.c");
+            builder.push($kcols.len() + n);
+        }
+
+        //version statement section
+        if let Some((_, vc)) = $version {
+            builder.push(",");
+            builder.push($crate::model::quote(vc, QUOTE));
+            builder.push("=");
+            builder.push($crate::model::quote(vc, QUOTE));
+            builder.push("+1");
+        }
+
+        let mut args = String::new();
+
+        //updated time statement section
+        if let Some((uc, clock)) = $my.model.updated_column() {
+            builder.push(",");
+            builder.push($crate::model::quote(uc, QUOTE));
+            builder.push("=");
+            sqlx_push_value!(
+                $my,
+                builder,
+                args,
+                &(),
+                false,
+                $crate::model::Stamp::Clock(clock)
+            );
+        }
+
+        //from statement section
+        builder.push(" from (");
+        sqlx_update_many_rows!($my, $rows, builder, args, $cols);
+        builder.push(") v");
+
+        //where statement section
+        builder.push(" where ");
+        for (n, (_, co)) in $kcols.iter().chain($version.iter()).enumerate() {
+            if n > 0 {
+                builder.push(" and ");
+            }
+            builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+            builder.push(".");
+            builder.push($crate::model::quote(co, QUOTE));
+            builder.push("=v.c");
+            if n < $kcols.len() {
+                builder.push(n);
+            } else {
+                builder.push($kcols.len() + $vcols.len());
+            }
         }
 
         //execute sql statements
-        let res = match builder.build().execute($my.executor).await {
+        let mut sql = builder.sql().to_string();
+        let arguments = $crate::model::arguments(builder.build())?;
+        let (trace, binds) = $my.model.trace(
+            $crate::model::Kind::Update,
+            false,
+            &mut sql,
+            &args,
+            sqlx::Arguments::len(&arguments),
+        )?;
+        let res = match timed!(
+            $my.model.deadline(),
+            trace,
+            $crate::model::statement(&sql, arguments, binds)?.execute($executor),
+            &sql
+        ) {
             Ok(res) => res.rows_affected(),
-            Err(err) => {
-                return Err($crate::anyhow!(
-                    "sql:`{}` args:[{}]  {}",
-                    builder.sql(),
-                    args,
-                    err
-                ))
+            Err(err) => return Err(trace.fail($crate::model::sql_error(&sql, args, err))),
+        };
+        trace.rows(res);
+
+        if $version.is_some() && res < $rows.len() as u64 {
+            return Err($crate::Error::StaleEntity($crate::StaleEntity {
+                table: $my.model.table.clone(),
+            }));
+        }
+
+        res
+    }};
+}
+
+#[cfg(feature = "mysql")]
+macro_rules! sqlx_update_many_join {
+    ($my:ident, $executor:expr, $rows:ident, $kcols:ident, $vcols:ident, $version:ident, $cols:ident) => {{
+        let mut builder = QueryBuilder::new("update ");
+        builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+
+        let mut args = String::new();
+
+        //join statement section
+        builder.push(" join (");
+        sqlx_update_many_rows!($my, $rows, builder, args, $cols);
+        builder.push(") v on ");
+        for (n, (_, co)) in $kcols.iter().chain($version.iter()).enumerate() {
+            if n > 0 {
+                builder.push(" and ");
             }
+            builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+            builder.push(".");
+            builder.push($crate::model::quote(co, QUOTE));
+            builder.push("=v.c");
+            if n < $kcols.len() {
+                builder.push(n);
+            } else {
+                builder.push($kcols.len() + $vcols.len());
+            }
+        }
+
+        //update statement section
+        builder.push(" set ");
+        for (n, (_, co)) in $vcols.iter().enumerate() {
+            if n > 0 {
+                builder.push(",");
+            }
+            builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+            builder.push(".");
+            builder.push($crate::model::quote(co, QUOTE));
+            builder.push("=v.c");
+            builder.push($kcols.len() + n);
+        }
+
+        //version statement section
+        if let Some((_, vc)) = $version {
+            builder.push(",");
+            builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+            builder.push(".");
+            builder.push($crate::model::quote(vc, QUOTE));
+            builder.push("=");
+            builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+            builder.push(".");
+            builder.push($crate::model::quote(vc, QUOTE));
+            builder.push("+1");
+        }
+
+        //updated time statement section
+        if let Some((uc, clock)) = $my.model.updated_column() {
+            builder.push(",");
+            builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+            builder.push(".");
+            builder.push($crate::model::quote(uc, QUOTE));
+            builder.push("=");
+            sqlx_push_value!(
+                $my,
+                builder,
+                args,
+                &(),
+                false,
+                $crate::model::Stamp::Clock(clock)
+            );
+        }
+
+        //execute sql statements
+        let mut sql = builder.sql().to_string();
+        let arguments = $crate::model::arguments(builder.build())?;
+        let (trace, binds) = $my.model.trace(
+            $crate::model::Kind::Update,
+            false,
+            &mut sql,
+            &args,
+            sqlx::Arguments::len(&arguments),
+        )?;
+        let res = match timed!(
+            $my.model.deadline(),
+            trace,
+            $crate::model::statement(&sql, arguments, binds)?.execute($executor),
+            &sql
+        ) {
+            Ok(res) => res.rows_affected(),
+            Err(err) => return Err(trace.fail($crate::model::sql_error(&sql, args, err))),
         };
+        trace.rows(res);
+
+        if $version.is_some() && res < $rows.len() as u64 {
+            return Err($crate::Error::StaleEntity($crate::StaleEntity {
+                table: $my.model.table.clone(),
+            }));
+        }
 
         res
     }};
@@ -340,10 +1218,34 @@ macro_rules! sqlx_update {
 
 #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 macro_rules! sqlx_delete {
-    ($my:ident, $filter:ident) => {{
+    ($my:ident, $filter:ident, $hard:expr) => {
+        sqlx_delete!($my, $filter, $hard, sqlx_execute)
+    };
+    ($my:ident, $filter:ident, $hard:expr, $run:ident) => {{
+        //soft delete updates the deleted mark instead
+        let soft = if $hard {
+            None
+        } else {
+            $my.model.soft_delete.as_ref()
+        };
+        let scope = soft.map(|sd| sd.condition(&$my.model.table, false, QUOTE));
+        let ids = $my.model.id_columns()?;
+        let more = scope.is_some() || !ids.is_empty();
+
         //from statement section
-        let mut builder = QueryBuilder::new("delete from ");
-        builder.push($my.model.table);
+        let mut builder = if let Some(sd) = soft {
+            let mut builder = QueryBuilder::new("update ");
+            builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+            builder.push(" set ");
+            builder.push($crate::model::quote(sd.column, QUOTE));
+            builder.push("=");
+            builder.push(sd.deleted);
+            builder
+        } else {
+            let mut builder = QueryBuilder::new("delete from ");
+            builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
+            builder
+        };
 
         let mut args = " ".to_string();
         let mut idx = 0;
@@ -351,17 +1253,23 @@ macro_rules! sqlx_delete {
         //where statement section
         if $filter.expr.len() > 0 {
             builder.push(" where ");
+            if more {
+                builder.push("(");
+            }
 
             let mut quo = false;
-            for ch in $filter.expr.chars() {
+            for ch in $crate::model::quote_expr(&$filter.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= $filter.args.len() {
-                        return Err($crate::anyhow!(" ? exceeds the number of args"));
+                        return Err($crate::Error::ArgCountMismatch);
                     }
-                    args.push_str(&($my.to_arg)(&*$filter.args[idx], &mut builder)?);
+                    let field = $my.model.redacted_filter(builder.sql());
+                    let arg = ($my.to_arg)(&*$filter.args[idx], &mut builder)
+                        .map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                    args.push_str(&$crate::model::shown(arg, &*$filter.args[idx], field));
                     args.push(' ');
                     idx += 1;
                 } else {
@@ -369,26 +1277,24 @@ macro_rules! sqlx_delete {
                 }
             }
             debug_assert!(!quo, "where statement quotation mark not closed error");
+            if more {
+                builder.push(")");
+            }
         }
+        sqlx_push_conditions!($my, builder, args, scope, ids, !$filter.expr.is_empty());
 
         if idx != $filter.args.len() {
-            return Err($crate::anyhow!(
-                "the quantity of ? is not equal to the number of parameters"
-            ));
+            return Err($crate::Error::ArgCountMismatch);
         }
 
         //execute sql statements
-        let res = match builder.build().execute($my.executor).await {
-            Ok(res) => res.rows_affected(),
-            Err(err) => {
-                return Err($crate::anyhow!(
-                    "sql:`{}` args:[{}]  {}",
-                    builder.sql(),
-                    args,
-                    err
-                ))
-            }
-        };
+        let (res, _) = $run!(
+            $my,
+            builder,
+            args,
+            $crate::model::Kind::Delete,
+            soft.is_some()
+        );
 
         res
     }};
@@ -397,16 +1303,19 @@ macro_rules! sqlx_delete {
 #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 macro_rules! sqlx_count {
     ($my:ident, $filter:ident, $other:ident) => {{
+        let (head, timeout) = select_head($my.model.timeout);
+        let deadline = $crate::model::Deadline::new(timeout);
+
         //determine if there is group by
         let mut builder = if matches!(&$other,Some(ot) if ot.group_by.len() > 0) {
-            QueryBuilder::new("select count(*) from (select 1")
+            QueryBuilder::new(head + "count(*) from (select 1")
         } else {
-            QueryBuilder::new("select count(*)")
+            QueryBuilder::new(head + "count(*)")
         };
 
         //from statement section
         builder.push(" from ");
-        builder.push($my.model.table);
+        builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
 
         let mut args = " ".to_string();
         let mut idx = 0;
@@ -423,9 +1332,12 @@ macro_rules! sqlx_count {
                     }
                     if ch == '?' && !quo {
                         if idx >= $filter.args.len() {
-                            return Err($crate::anyhow!(" ? exceeds the number of args"));
+                            return Err($crate::Error::ArgCountMismatch);
                         }
-                        args.push_str(&($my.to_arg)(&*$filter.args[idx], &mut builder)?);
+                        let field = $my.model.redacted_filter(builder.sql());
+                        let arg = ($my.to_arg)(&*$filter.args[idx], &mut builder)
+.map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                        args.push_str(&$crate::model::shown(arg, &*$filter.args[idx], field));
                         args.push(' ');
                         idx += 1;
                     } else {
@@ -437,25 +1349,38 @@ macro_rules! sqlx_count {
         debug_assert!(!quo, "join on statement quotation mark not closed error");
 
         //where statement section
+        let scope = $my.model.scope(QUOTE);
+        let ids = $my.model.id_columns()?;
+        let more = scope.is_some() || !ids.is_empty();
         if $filter.expr.len() > 0 {
             builder.push(" where ");
+            if more {
+                builder.push("(");
+            }
 
-            for ch in $filter.expr.chars() {
+            for ch in $crate::model::quote_expr(&$filter.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= $filter.args.len() {
-                        return Err($crate::anyhow!(" ? exceeds the number of args"));
+                        return Err($crate::Error::ArgCountMismatch);
                     }
-                    args.push_str(&($my.to_arg)(&*$filter.args[idx], &mut builder)?);
+                    let field = $my.model.redacted_filter(builder.sql());
+                    let arg = ($my.to_arg)(&*$filter.args[idx], &mut builder)
+.map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                    args.push_str(&$crate::model::shown(arg, &*$filter.args[idx], field));
                     args.push(' ');
                     idx += 1;
                 } else {
                     builder.push(ch);
                 }
             }
+            if more {
+                builder.push(")");
+            }
         }
+        sqlx_push_conditions!($my, builder, args, scope, ids, !$filter.expr.is_empty());
         debug_assert!(!quo, "where statement quotation mark not closed error");
 
         //group by statement section
@@ -474,9 +1399,12 @@ macro_rules! sqlx_count {
                         }
                         if ch == '?' && !quo {
                             if idx >= $filter.args.len() {
-                                return Err($crate::anyhow!(" ? exceeds the number of args"));
+                                return Err($crate::Error::ArgCountMismatch);
                             }
-                            args.push_str(&($my.to_arg)(&*$filter.args[idx], &mut builder)?);
+                            let field = $my.model.redacted_filter(builder.sql());
+                            let arg = ($my.to_arg)(&*$filter.args[idx], &mut builder)
+.map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                            args.push_str(&$crate::model::shown(arg, &*$filter.args[idx], field));
                             args.push(' ');
                             idx += 1;
                         } else {
@@ -491,16 +1419,19 @@ macro_rules! sqlx_count {
         debug_assert!(!quo, "having statement quotation mark not closed error");
 
         if idx != $filter.args.len() {
-            return Err($crate::anyhow!(
-                "the quantity of ? is not equal to the number of parameters"
-            ));
+            return Err($crate::Error::ArgCountMismatch);
         }
 
         //execute sql statements
-        let row = match builder.build().fetch_one($my.executor).await {
+        let mut sql = builder.sql().to_string();
+        let arguments = $crate::model::arguments(builder.build())?;
+        let (trace, binds) =
+            $my.model.trace($crate::model::Kind::Select, false, &mut sql, &args, sqlx::Arguments::len(&arguments))?;
+        let row = match timed!(deadline, trace, $crate::model::statement(&sql, arguments, binds)?.fetch_one($crate::model::Bound::executor($my.executor)), &sql) {
             Ok(row) => row,
-            Err(err) => return Err($crate::anyhow!("sql:`{}` args:[{}]  {}", builder.sql(), args, err)),
+            Err(err) => return Err(trace.fail($crate::model::sql_error(&sql, args, err))),
         };
+        trace.rows(1);
 
         row.try_get(0)?
     }};
@@ -509,37 +1440,16 @@ macro_rules! sqlx_count {
 #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 macro_rules! sqlx_query {
     ($my:ident, $filter:ident, $other:ident, $limit:ident) => {{
-        let (_, fnames) = !$my.model.entity;
-
-        let mut builder = QueryBuilder::new("select ");
+        let (head, timeout) = select_head($my.model.timeout);
+        let deadline = $crate::model::Deadline::new(timeout);
+        let mut builder = QueryBuilder::new(head);
 
         //select statement section
-        let mut sep = false;
-        for fd in fnames {
-            if let Some(&co) = $my.model.fields.get(fd) {
-                if co.len() > 0 {
-                    if co != "-" {
-                        if sep {
-                            builder.push(",");
-                        }
-                        builder.push(co);
-                        builder.push(" as ");
-                        builder.push(fd);
-                        sep = true;
-                    }
-                } else {
-                    if sep {
-                        builder.push(",");
-                    }
-                    builder.push(fd);
-                    sep = true;
-                }
-            }
-        }
+        builder.push($my.model.select_list(QUOTE));
 
         //from statement section
         builder.push(" from ");
-        builder.push($my.model.table);
+        builder.push($crate::model::quote_table(&$my.model.table, QUOTE));
 
         let mut args = " ".to_string();
         let mut idx = 0;
@@ -556,9 +1466,12 @@ macro_rules! sqlx_query {
                     }
                     if ch == '?' && !quo {
                         if idx >= $filter.args.len() {
-                            return Err($crate::anyhow!(" ? exceeds the number of args"));
+                            return Err($crate::Error::ArgCountMismatch);
                         }
-                        args.push_str(&($my.to_arg)(&*$filter.args[idx], &mut builder)?);
+                        let field = $my.model.redacted_filter(builder.sql());
+                        let arg = ($my.to_arg)(&*$filter.args[idx], &mut builder)
+                            .map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                        args.push_str(&$crate::model::shown(arg, &*$filter.args[idx], field));
                         args.push(' ');
                         idx += 1;
                     } else {
@@ -570,25 +1483,38 @@ macro_rules! sqlx_query {
         debug_assert!(!quo, "join on statement quotation mark not closed error");
 
         //where statement section
+        let scope = $my.model.scope(QUOTE);
+        let ids = $my.model.id_columns()?;
+        let more = scope.is_some() || !ids.is_empty();
         if $filter.expr.len() > 0 {
             builder.push(" where ");
+            if more {
+                builder.push("(");
+            }
 
-            for ch in $filter.expr.chars() {
+            for ch in $crate::model::quote_expr(&$filter.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= $filter.args.len() {
-                        return Err($crate::anyhow!(" ? exceeds the number of args"));
+                        return Err($crate::Error::ArgCountMismatch);
                     }
-                    args.push_str(&($my.to_arg)(&*$filter.args[idx], &mut builder)?);
+                    let field = $my.model.redacted_filter(builder.sql());
+                    let arg = ($my.to_arg)(&*$filter.args[idx], &mut builder)
+                        .map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                    args.push_str(&$crate::model::shown(arg, &*$filter.args[idx], field));
                     args.push(' ');
                     idx += 1;
                 } else {
                     builder.push(ch);
                 }
             }
+            if more {
+                builder.push(")");
+            }
         }
+        sqlx_push_conditions!($my, builder, args, scope, ids, !$filter.expr.is_empty());
         debug_assert!(!quo, "where statement quotation mark not closed error");
 
         //group by statement section
@@ -607,9 +1533,12 @@ macro_rules! sqlx_query {
                         }
                         if ch == '?' && !quo {
                             if idx >= $filter.args.len() {
-                                return Err($crate::anyhow!(" ? exceeds the number of args"));
+                                return Err($crate::Error::ArgCountMismatch);
                             }
-                            args.push_str(&($my.to_arg)(&*$filter.args[idx], &mut builder)?);
+                            let field = $my.model.redacted_filter(builder.sql());
+                            let arg = ($my.to_arg)(&*$filter.args[idx], &mut builder)
+                                .map_err(|err| $crate::model::arg_error(builder.sql(), err))?;
+                            args.push_str(&$crate::model::shown(arg, &*$filter.args[idx], field));
                             args.push(' ');
                             idx += 1;
                         } else {
@@ -622,9 +1551,7 @@ macro_rules! sqlx_query {
         debug_assert!(!quo, "having statement quotation mark not closed error");
 
         if idx != $filter.args.len() {
-            return Err($crate::anyhow!(
-                "the quantity of ? is not equal to the number of parameters"
-            ));
+            return Err($crate::Error::ArgCountMismatch);
         }
 
         //order by statement section
@@ -644,35 +1571,34 @@ macro_rules! sqlx_query {
         }
 
         //query column section
-        let fds = fnames
-            .iter()
-            .enumerate()
-            .filter_map(|(ix, fd)| {
-                if let Some(&co) = $my.model.fields.get(fd) {
-                    if co == "-" {
-                        return None;
-                    }
-                }
-                Some((ix, fd))
-            })
-            .collect::<Vec<_>>();
+        let fds = $my.model.read_fields();
 
         //execute sql statements
         let mut res = Vec::new();
-        let sql = builder.sql().to_string();
-        let mut stream = builder.build().fetch($my.executor);
-        while let Some(rst) = stream.next().await {
+        let mut sql = builder.sql().to_string();
+        let arguments = $crate::model::arguments(builder.build())?;
+        let (trace, binds) = $my.model.trace(
+            $crate::model::Kind::Select,
+            false,
+            &mut sql,
+            &args,
+            sqlx::Arguments::len(&arguments),
+        )?;
+        let mut stream = $crate::model::statement(&sql, arguments, binds)?
+            .fetch($crate::model::Bound::executor($my.executor));
+        while let Some(rst) = timed!(deadline, trace, stream.next(), &sql) {
             match rst {
                 Ok(row) => {
                     let mut entity = $my.model.entity.clone();
-                    for (ix, fd) in &fds {
+                    for (ix, fd) in fds.iter() {
                         ($my.from_row)(fd, &row, &mut entity[*ix])?;
                     }
                     res.push(entity);
                 }
-                Err(err) => return Err(crate::anyhow!("sql:`{}` args:[{}]  {}", sql, args, err)),
+                Err(err) => return Err(trace.fail($crate::model::sql_error(&sql, args, err))),
             }
         }
+        trace.rows(res.len() as u64);
 
         res
     }};
@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock, RwLock,
+    },
+};
+
+use super::{naming::naming_of, quote, Quote};
+
+/// identifier quotes of all dialects, the column list is rendered for each
+const QUOTES: [Quote; 4] = [
+    ('`', '`', false),
+    ('"', '"', true),
+    ('"', '"', false),
+    ('[', ']', false),
+];
+
+/// metadata of an entity type, computed once and shared by all its models
+pub(crate) struct ModelMeta {
+    /// database table name
+    pub(crate) table: String,
+    /// all fields, (field index, field name)
+    pub(crate) fields: Vec<(usize, &'static str)>,
+    /// quoted column list of each dialect
+    columns: Vec<(Quote, String)>,
+}
+
+impl ModelMeta {
+    fn new(name: &str, fnames: &'static [&'static str]) -> Self {
+        let columns = QUOTES
+            .iter()
+            .map(|&q| {
+                let list = fnames
+                    .iter()
+                    .map(|fd| quote(fd, q))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                (q, list)
+            })
+            .collect();
+        ModelMeta {
+            table: naming_of(name).table(name),
+            fields: fnames.iter().copied().enumerate().collect(),
+            columns,
+        }
+    }
+
+    /// column list of all fields without overrides, used by select and insert
+    pub(crate) fn columns(&self, q: Quote) -> Option<&str> {
+        self.columns
+            .iter()
+            .find(|(cq, _)| *cq == q)
+            .map(|(_, list)| list.as_str())
+    }
+}
+
+type MetaKey = (&'static str, &'static [&'static str]);
+
+/// cached metadata of one entity type
+struct MetaEntry {
+    /// naming generation the current metadata was resolved in
+    generation: u64,
+    /// metadata under the naming of that generation
+    current: &'static ModelMeta,
+    /// metadata of every table name the type had, reused when a naming comes back
+    tables: Vec<&'static ModelMeta>,
+}
+
+static METAS: OnceLock<RwLock<HashMap<MetaKey, MetaEntry>>> = OnceLock::new();
+
+/// bumped by every naming change, stale entries resolve their table name again
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// cached metadata of an entity type by its struct name and fields
+pub(crate) fn meta_of(name: &'static str, fnames: &'static [&'static str]) -> &'static ModelMeta {
+    let metas = METAS.get_or_init(Default::default);
+    let generation = GENERATION.load(Ordering::Acquire);
+    if let Some(entry) = metas
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&(name, fnames))
+    {
+        if entry.generation == generation {
+            return entry.current;
+        }
+    }
+    let mut metas = metas.write().unwrap_or_else(|e| e.into_inner());
+    let entry = metas.entry((name, fnames)).or_insert_with(|| {
+        let meta: &'static ModelMeta = Box::leak(Box::new(ModelMeta::new(name, fnames)));
+        MetaEntry {
+            generation,
+            current: meta,
+            tables: vec![meta],
+        }
+    });
+    if entry.generation != generation {
+        //a meta is leaked once per distinct table name, not per naming change
+        let table = naming_of(name).table(name);
+        entry.current = match entry.tables.iter().find(|meta| meta.table == table) {
+            Some(meta) => meta,
+            None => {
+                let meta: &'static ModelMeta = Box::leak(Box::new(ModelMeta::new(name, fnames)));
+                entry.tables.push(meta);
+                meta
+            }
+        };
+        entry.generation = generation;
+    }
+    entry.current
+}
+
+/// make the cached metadata resolve its table name again under a new naming
+pub(crate) fn clear_metas() {
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+}
@@ -0,0 +1,252 @@
+use crate::{Error, Result};
+
+/// operation invoked on a model, each statement it runs is reported under it
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Kind {
+    Select,
+    Insert,
+    Update,
+    /// soft deletes too, see [`Statement::soft`]
+    Delete,
+}
+
+impl Kind {
+    /// lowercase name of the kind
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Select => "select",
+            Kind::Insert => "insert",
+            Kind::Update => "update",
+            Kind::Delete => "delete",
+        }
+    }
+}
+
+/// statement built by a model, seen by its middleware
+#[derive(Debug)]
+pub struct Statement<'s> {
+    pub kind: Kind,
+    /// the delete updates the soft delete mark instead of removing the data
+    pub soft: bool,
+    /// table of the model
+    pub table: &'s str,
+    /// sql text, rewritten by the middleware before execution
+    pub sql: String,
+    /// bound arguments, redacted as in errors
+    pub args: &'s str,
+    /// number of arguments bound by the model
+    pub params: usize,
+    /// arguments bound after those of the model, the sql refers to them with the
+    /// placeholders of the backend numbered from `params + 1`: `$n` on Postgres,
+    /// `@Pn` on MsSQL and `?` on MySQL and SQLite
+    pub binds: Vec<Arg>,
+}
+
+/// argument bound by a middleware
+#[derive(Clone, Debug, PartialEq)]
+pub enum Arg {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl From<bool> for Arg {
+    fn from(value: bool) -> Self {
+        Arg::Bool(value)
+    }
+}
+
+impl From<i64> for Arg {
+    fn from(value: i64) -> Self {
+        Arg::Int(value)
+    }
+}
+
+impl From<i32> for Arg {
+    fn from(value: i32) -> Self {
+        Arg::Int(value.into())
+    }
+}
+
+impl From<f64> for Arg {
+    fn from(value: f64) -> Self {
+        Arg::Float(value)
+    }
+}
+
+impl From<String> for Arg {
+    fn from(value: String) -> Self {
+        Arg::Text(value)
+    }
+}
+
+impl From<&str> for Arg {
+    fn from(value: &str) -> Self {
+        Arg::Text(value.to_string())
+    }
+}
+
+#[cfg(feature = "mssql")]
+impl tiberius::ToSql for Arg {
+    fn to_sql(&self) -> tiberius::ColumnData<'_> {
+        match self {
+            Arg::Bool(value) => value.to_sql(),
+            Arg::Int(value) => value.to_sql(),
+            Arg::Float(value) => value.to_sql(),
+            Arg::Text(value) => value.to_sql(),
+        }
+    }
+}
+
+/// outcome of a statement
+#[derive(Clone, Copy, Debug)]
+pub enum Outcome<'e> {
+    /// rows changed by a write or returned by a query
+    Rows(u64),
+    /// error of the database
+    Failed(&'e Error),
+    /// the statement was left unfinished by a timeout, a conversion error or a
+    /// dropped future
+    Dropped,
+}
+
+/// hook around every statement of a model, registered before bind
+/// # Example
+/// ```no_run
+/// struct ReadOnly;
+///
+/// impl Middleware for ReadOnly {
+///     fn before(&self, statement: &mut Statement<'_>) -> Result<()> {
+///         if statement.kind == Kind::Delete {
+///             return Err(anyhow!("delete from `{}` is refused", statement.table).into());
+///         }
+///         statement.sql.insert_str(0, "/* request_id=42 */ ");
+///         Ok(())
+///     }
+/// }
+///
+/// struct Tenant(i64);
+///
+/// impl Middleware for Tenant {
+///     fn before(&self, statement: &mut Statement<'_>) -> Result<()> {
+///         if statement.kind == Kind::Delete {
+///             statement.binds.push(Arg::from(self.0));
+///             let n = statement.params + statement.binds.len();
+///             statement.sql.push_str(&format!(" and tenant_id = ${}", n));
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let res = Model::new(&oplog)
+///     .middleware(&ReadOnly)
+///     .bind(&pool)
+///     .delete(&filter)
+///     .await;
+/// ```
+pub trait Middleware: Send + Sync {
+    /// called before execution, the sql may be rewritten and an error rejects
+    /// the statement, the arguments stay bound in their order and `binds` follow
+    fn before(&self, statement: &mut Statement<'_>) -> Result<()> {
+        let _ = statement;
+        Ok(())
+    }
+
+    /// called with the outcome after execution
+    fn after(&self, statement: &Statement<'_>, outcome: Outcome<'_>) {
+        let _ = (statement, outcome);
+    }
+}
+
+/// arguments bound by the builder of a query
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(crate) fn arguments<'q, DB, A>(
+    mut query: sqlx::query::Query<'q, DB, A>,
+) -> Result<DB::Arguments<'q>>
+where
+    DB: sqlx::Database,
+    A: Send + sqlx::IntoArguments<'q, DB>,
+{
+    Ok(sqlx::Execute::take_arguments(&mut query)
+        .map_err(|err| anyhow::anyhow!(err))?
+        .unwrap_or_default())
+}
+
+/// query of the sql of the statement, which the middleware may have rewritten,
+/// with the arguments of the builder followed by those of the middleware
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(crate) fn statement<'q, A>(
+    sql: &'q str,
+    mut arguments: A,
+    binds: Vec<Arg>,
+) -> Result<sqlx::query::Query<'q, A::Database, A>>
+where
+    A: sqlx::Arguments<'q> + sqlx::IntoArguments<'q, A::Database>,
+    bool: sqlx::Encode<'q, A::Database> + sqlx::Type<A::Database>,
+    i64: sqlx::Encode<'q, A::Database> + sqlx::Type<A::Database>,
+    f64: sqlx::Encode<'q, A::Database> + sqlx::Type<A::Database>,
+    String: sqlx::Encode<'q, A::Database> + sqlx::Type<A::Database>,
+{
+    for bind in binds {
+        match bind {
+            Arg::Bool(value) => arguments.add(value),
+            Arg::Int(value) => arguments.add(value),
+            Arg::Float(value) => arguments.add(value),
+            Arg::Text(value) => arguments.add(value),
+        }
+        .map_err(|err| anyhow::anyhow!(err))?;
+    }
+    Ok(sqlx::query_with(sql, arguments))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::{super::trace::Trace, *};
+
+    struct Seen(Mutex<Vec<(Kind, bool, String)>>);
+
+    impl Middleware for Seen {
+        fn after(&self, statement: &Statement<'_>, _: Outcome<'_>) {
+            let seen = (statement.kind, statement.soft, statement.sql.clone());
+            self.0.lock().unwrap().push(seen);
+        }
+    }
+
+    #[test]
+    fn kinds() {
+        let seen = Seen(Mutex::new(Vec::new()));
+        let middleware: [&dyn Middleware; 1] = [&seen];
+        let sql = "update oplog set deleted=1";
+        Trace::start((Kind::Delete, true), "oplog", &middleware, sql, " ", 0, &[]).rows(1);
+        let sql = "delete from oplog";
+        Trace::start(
+            (Kind::Delete, false),
+            "oplog",
+            &middleware,
+            sql,
+            " ",
+            0,
+            &[],
+        )
+        .rows(1);
+        assert_eq!(
+            *seen.0.lock().unwrap(),
+            vec![
+                (Kind::Delete, true, "update oplog set deleted=1".to_string()),
+                (Kind::Delete, false, "delete from oplog".to_string()),
+            ]
+        );
+        assert_eq!(Kind::Delete.as_str(), "delete");
+    }
+
+    #[test]
+    fn args() {
+        assert_eq!(Arg::from(7), Arg::Int(7));
+        assert_eq!(Arg::from("acme"), Arg::Text("acme".to_string()));
+        assert_eq!(Arg::from(true), Arg::Bool(true));
+    }
+}
@@ -1,45 +1,571 @@
 use std::{
     any::Any,
+    borrow::Cow,
     collections::HashMap,
+    fmt,
     ops::{IndexMut, Not},
+    time::Duration,
 };
 
+use crate::{anyhow, Error, Filter, Result, RowCount, RowCountMismatch, Source, Timeout};
+
 #[macro_use]
 mod macros;
 
+mod naming;
+pub use naming::{set_entity_naming, set_naming, Naming, NamingStrategy};
+
+mod meta;
+use meta::{meta_of, ModelMeta};
+
+mod owned;
+pub use owned::{Backend, Conv, OwnedModel, OwnedQuery};
+
+mod repo;
+pub use repo::{Repo, RepoQuery};
+
+mod dynamic;
+pub use dynamic::{BoxFuture, DynExecutor};
+
+mod transaction;
+pub use transaction::{transaction, transaction_retry, transaction_with, Isolation, Transact};
+
+mod retry;
+pub use retry::{is_retryable, retry, Retry};
+pub(crate) use retry::{sleep, Attempts};
+
+mod timeout;
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+pub(crate) use timeout::millis;
+pub(crate) use timeout::{is_timeout, Deadline};
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+mod bound;
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+pub(crate) use bound::Plain;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(crate) use bound::{Acquired, Bound};
+
+mod redact;
+pub(crate) use redact::{filter_field, shown};
+pub use redact::{redact_type, set_redaction, Redaction, MASK};
+
+mod middleware;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(crate) use middleware::{arguments, statement};
+pub use middleware::{Arg, Kind, Middleware, Outcome, Statement};
+
+mod trace;
+#[cfg(feature = "tracing")]
+pub use trace::set_slow_threshold;
+pub(crate) use trace::Trace;
+
+#[cfg(feature = "any")]
+mod anydb;
+#[cfg(all(feature = "any", feature = "mssql"))]
+pub use anydb::mssql_config;
+#[cfg(feature = "any")]
+pub use anydb::{connect, AnyDb};
+
 #[cfg(feature = "postgres")]
 mod postgres;
 #[cfg(feature = "postgres")]
-pub use postgres::Postgres;
+pub use postgres::{Postgres, PostgresConv};
 
 #[cfg(feature = "mysql")]
 mod mysql;
 #[cfg(feature = "mysql")]
-pub use mysql::Mysql;
+pub use mysql::{Mysql, MysqlConv};
 
 #[cfg(feature = "sqlite")]
 mod sqlite;
 #[cfg(feature = "sqlite")]
-pub use sqlite::Sqlite;
+pub use sqlite::{Sqlite, SqliteConv};
 
 #[cfg(feature = "mssql")]
 mod mssql;
 #[cfg(feature = "mssql")]
-pub use mssql::Mssql;
+pub use mssql::{Mssql, MssqlConv, MssqlTx};
+
+/// opening and closing identifier quotes of a dialect, and whether it folds
+/// unquoted names to lowercase
+pub(crate) type Quote = (char, char, bool);
+
+/// quote a plain identifier, expressions and qualified names stay untouched, so do
+/// names with uppercase letters where unquoted names are folded, they keep matching
+/// the columns they matched unquoted
+pub(crate) fn quote(name: &str, q: Quote) -> Cow<'_, str> {
+    if is_identifier(name) && !(q.2 && name.chars().any(|ch| ch.is_ascii_uppercase())) {
+        Cow::Owned(format!("{}{}{}", q.0, name, q.1))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// quote each part of a table name qualified by its schema
+pub(crate) fn quote_table(name: &str, q: Quote) -> Cow<'_, str> {
+    if name.split('.').all(is_identifier) {
+        let parts = name
+            .split('.')
+            .map(|part| quote(part, q))
+            .collect::<Vec<_>>();
+        Cow::Owned(parts.join("."))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// quote the names of a filter expression between backticks, as written by
+/// `expr!`, by the dialect, string literals stay untouched
+pub(crate) fn quote_expr(expr: &str, q: Quote) -> Cow<'_, str> {
+    if q.0 == '`' || !expr.contains('`') {
+        return Cow::Borrowed(expr);
+    }
+    let mut res = String::with_capacity(expr.len());
+    let mut quo = false;
+    let mut rest = expr;
+    while let Some(i) = rest.find(['\'', '`']) {
+        res.push_str(&rest[..i]);
+        if rest[i..].starts_with('\'') || quo {
+            quo ^= rest[i..].starts_with('\'');
+            res.push_str(&rest[i..=i]);
+            rest = &rest[i + 1..];
+            continue;
+        }
+        let name = match rest[i + 1..].find('`') {
+            Some(j) => &rest[i + 1..i + 1 + j],
+            None => {
+                rest = &rest[i..];
+                break;
+            }
+        };
+        if is_identifier(name) {
+            res.push_str(&quote(name, q));
+        } else {
+            res.push(q.0);
+            res.push_str(name);
+            res.push(q.1);
+        }
+        rest = &rest[i + name.len() + 2..];
+    }
+    res.push_str(rest);
+    Cow::Owned(res)
+}
+
+/// letters, digits and underlines not starting with a digit
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(ch) if ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// filter without conditions, only used by update_all and delete_all
+pub(crate) static ALL: Filter = Filter {
+    expr: String::new(),
+    args: Vec::new(),
+};
+
+/// refuse to update or delete the whole table by an empty filter
+pub(crate) fn check_filter(filter: &Filter, op: &str) -> Result<()> {
+    if filter.expr.trim().is_empty() {
+        return Err(anyhow!(
+            "{} without filter conditions is refused, use {}_all to {} all data",
+            op,
+            op,
+            op
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// field index and database column name
+pub(crate) type Columns<'a> = Vec<(usize, &'a str)>;
+
+/// soft delete mark of a data table
+/// # Example
+/// ```no_run
+/// model.soft_delete = Some(SoftDelete {
+///     column: "is_deleted",
+///     deleted: "1",
+///     undeleted: "0",
+/// });
+/// model.soft_delete = Some(SoftDelete {
+///     column: "deleted_at",
+///     deleted: "current_timestamp",
+///     undeleted: "null",
+/// });
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SoftDelete<'a> {
+    /// column of the deleted mark
+    pub column: &'a str,
+    /// sql value written by delete
+    pub deleted: &'a str,
+    /// sql value of data not deleted, `null` is compared by is null
+    pub undeleted: &'a str,
+}
+
+impl SoftDelete<'_> {
+    /// comparison of the deleted mark, data not deleted or deleted data
+    pub(crate) fn predicate(&self, deleted: bool) -> String {
+        if self.undeleted.eq_ignore_ascii_case("null") {
+            let op = if deleted { "is not null" } else { "is null" };
+            format!(" {}", op)
+        } else {
+            let op = if deleted { "<>" } else { "=" };
+            format!("{}{}", op, self.undeleted)
+        }
+    }
+
+    /// condition of data not deleted, or deleted data
+    pub(crate) fn condition(&self, table: &str, deleted: bool, q: Quote) -> String {
+        format!(
+            "{}.{}{}",
+            quote_table(table, q),
+            quote(self.column, q),
+            self.predicate(deleted)
+        )
+    }
+}
+
+/// source of the automatic timestamps
+#[derive(Clone, Copy, Debug)]
+pub enum Clock<'a> {
+    /// current time of the database, now() getdate() or current_timestamp
+    Database,
+    /// time value of the application clock, bound as an argument
+    Value(&'a (dyn Any + Send + Sync)),
+}
+
+/// fields filled with the current time automatically
+/// # Example
+/// ```no_run
+/// model.timestamps = Some(Timestamps {
+///     created: Some(field!(oplog.created_at)),
+///     updated: Some(field!(oplog.optime)),
+///     clock: Clock::Database,
+/// });
+/// // deterministic time in tests
+/// let now = NaiveDateTime::default();
+/// model.timestamps = Some(Timestamps {
+///     created: None,
+///     updated: Some(field!(oplog.optime)),
+///     clock: Clock::Value(&now),
+/// });
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Timestamps<'a> {
+    /// field filled by insert and never changed by update
+    pub created: Option<&'a str>,
+    /// field filled by insert and update
+    pub updated: Option<&'a str>,
+    /// source of the time
+    pub clock: Clock<'a>,
+}
+
+/// model bound to the connection of a transaction
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+pub(crate) struct TxModel<'a, 't, T, C, P, R> {
+    pub(crate) model: Model<'a, T>,
+    pub(crate) executor: &'t mut C,
+    pub(crate) to_arg: P,
+    pub(crate) from_row: R,
+    /// query sections of the statement run in the transaction
+    pub(crate) order: &'a str,
+    pub(crate) limit: &'a i64,
+    pub(crate) offset: &'a i64,
+}
+
+/// result of a write with the number of changed rows
+pub(crate) trait Affected {
+    fn affected(&self) -> u64;
+}
+
+impl Affected for u64 {
+    fn affected(&self) -> u64 {
+        *self
+    }
+}
+
+impl<T> Affected for Vec<T> {
+    fn affected(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+/// rows changed by each batch of update_many
+pub(crate) struct Batches(pub(crate) Vec<u64>);
+
+impl Affected for Batches {
+    fn affected(&self) -> u64 {
+        self.0.iter().sum()
+    }
+}
+
+/// rows of a batch of update_many within the parameter limit of the backend,
+/// one parameter is kept for the updated time
+pub(crate) fn batch_rows(params: usize, cols: usize) -> usize {
+    ((params - 1) / cols.max(1)).max(1)
+}
+
+/// error of a write that changed an unexpected number of rows
+pub(crate) fn check_rows(table: &str, expect: Option<RowCount>, rows: u64) -> Result<()> {
+    match expect {
+        Some(expected) if !expected.allows(rows) => {
+            Err(Error::RowCountMismatch(RowCountMismatch {
+                table: table.to_string(),
+                expected,
+                actual: rows,
+            }))
+        }
+        _ => Ok(()),
+    }
+}
 
+/// conversion error of an argument with the sql built up to it, errors of
+/// custom conversion functions become conversion errors too
+pub(crate) fn arg_error(sql: &str, err: Error) -> Error {
+    let (field, ty, source) = match err {
+        Error::Conversion {
+            field, ty, source, ..
+        } => (field, ty, source),
+        Error::Other(err) => (None, None, Some(err.into())),
+        err => return err,
+    };
+    Error::Conversion {
+        field,
+        ty,
+        source,
+        sql: Some(sql.to_string()),
+    }
+}
+
+/// error of a statement with its sql and args, timeouts and constraint
+/// violations are told apart by the codes of the backend and the database
+/// error is kept as the source so it can still be classified
+pub(crate) fn sql_error<E>(sql: impl fmt::Display, args: impl fmt::Display, err: E) -> Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    if is_timeout(&err) {
+        return Error::Timeout(Timeout::new(sql));
+    }
+    let sql = sql.to_string();
+    let (violation, constraint) = match violation_of(&err) {
+        Some(violation) => violation,
+        None => {
+            return Error::Database {
+                sql,
+                args: args.to_string(),
+                source: Box::new(err),
+            }
+        }
+    };
+    let source = Box::new(err);
+    match violation {
+        Violation::Unique => Error::UniqueViolation {
+            constraint,
+            sql,
+            source,
+        },
+        Violation::ForeignKey => Error::ForeignKeyViolation {
+            constraint,
+            sql,
+            source,
+        },
+        Violation::NotNull => Error::NotNullViolation {
+            constraint,
+            sql,
+            source,
+        },
+        Violation::Check => Error::CheckViolation {
+            constraint,
+            sql,
+            source,
+        },
+    }
+}
+
+/// constraint violated by a statement
+pub(crate) enum Violation {
+    Unique,
+    ForeignKey,
+    NotNull,
+    Check,
+}
+
+/// violated constraint of a database error and the name of the constraint
+fn violation_of(err: &(dyn std::error::Error + 'static)) -> Option<(Violation, Option<String>)> {
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    if let Some(sqlx::Error::Database(db)) = err.downcast_ref::<sqlx::Error>() {
+        let violation = match db.kind() {
+            sqlx::error::ErrorKind::UniqueViolation => Violation::Unique,
+            sqlx::error::ErrorKind::ForeignKeyViolation => Violation::ForeignKey,
+            sqlx::error::ErrorKind::NotNullViolation => Violation::NotNull,
+            sqlx::error::ErrorKind::CheckViolation => Violation::Check,
+            _ => return None,
+        };
+        return Some((violation, db.constraint().map(str::to_string)));
+    }
+    #[cfg(feature = "mssql")]
+    if let Some(err) = err.downcast_ref::<tiberius::error::Error>() {
+        return mssql::violation(err);
+    }
+    let _ = err;
+    None
+}
+
+/// value written to a field by insert or update
+pub(crate) enum Stamp<'a> {
+    /// value of the entity
+    Entity,
+    /// not written
+    Skip,
+    /// current time
+    Clock(Clock<'a>),
+}
+
+impl<'a, T> From<Model<'a, T>> for Source<'a>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+    &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+{
+    fn from(model: Model<'a, T>) -> Self {
+        let (_, fnames) = !model.entity;
+        let columns = fnames
+            .iter()
+            .filter(|&&fd| model.readable(fd))
+            .map(|&fd| (fd, model.column(fd)))
+            .collect();
+        Source {
+            scope: model.scope_predicate(),
+            table: model.table,
+            columns,
+        }
+    }
+}
+
+/// column of a field, the field name unless it is overridden
+pub(crate) fn column_of<'a>(fields: &HashMap<&'a str, &'a str>, fd: &'a str) -> &'a str {
+    match fields.get(fd) {
+        Some(&co) if !co.is_empty() && co != "-" => co,
+        _ => fd,
+    }
+}
+
+/// statements a field takes part in, fields without a role are read and written
+/// # Example
+/// ```no_run
+/// // joined column, select clazz.name as clazz_name
+/// model.fields.insert(field!(student.clazz_name), "clazz.name");
+/// model.roles.insert(field!(student.clazz_name), Role::ReadOnly);
+/// model.roles.insert(field!(student.created_by), Role::InsertOnly);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    /// read by queries, written by insert and update
+    ReadWrite,
+    /// only read by queries, computed or joined columns
+    ReadOnly,
+    /// written by insert and update, never read
+    WriteOnly,
+    /// only written by insert
+    InsertOnly,
+    /// read by queries and written by insert, excluded from update
+    NoUpdate,
+    /// not used by any statement, the same as mapping to `-`
+    Skip,
+}
+
+/// mappings declared next to the struct, implemented by `#[derive(Entity)]`
+/// and applied by [`Model::of`] and [`OwnedModel::of`], `new` leaves them out
+/// # Example
+/// ```no_run
+/// #[derive(Clone, Default, Entity)]
+/// #[crudx(table = "audit.oplog")]
+/// pub struct Oplog {
+///     #[crudx(key)]
+///     pub pid: i64,
+///     #[crudx(rename = "user")]
+///     pub user_id: String,
+///     #[crudx(read_only)]
+///     pub optime: String,
+/// }
+/// ```
+pub trait Entity {
+    /// table name, the naming is used when none
+    const TABLE: Option<&'static str> = None;
+    /// column overrides, (field name, column name), `-` skips the field
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[];
+    /// roles of the fields, (field name, role)
+    const ROLES: &'static [(&'static str, Role)] = &[];
+    /// primary key fields
+    const KEYS: &'static [&'static str] = &[];
+    /// version field of optimistic locking
+    const VERSION: Option<&'static str> = None;
+    /// fields whose values are masked in errors
+    const REDACT: &'static [&'static str] = &[];
+}
+
+#[cfg(feature = "derive")]
+pub use crudx_derive::Entity;
+
+/// soft deleted data visible to queries
+#[derive(Clone, Copy, PartialEq)]
+enum Trashed {
+    Exclude,
+    Include,
+    Only,
+}
+
+#[derive(Clone)]
 pub struct Model<'a, T> {
     entity: &'a T,
+    meta: &'static ModelMeta,
     /// database table name
     pub table: String,
-    /// database data table field names
+    /// column overrides of the fields, fields not in the map use their own names
     /// # Example
     /// ```no_run
     /// // skip name
-    /// *model.fields.get_mut("name").unwrap() = "-";
+    /// model.fields.insert("name", "-");
     /// // select clazzName as name
-    /// *model.fields.get_mut("name").unwrap() = "clazzName";
+    /// model.fields.insert("name", "clazzName");
     /// ```
     pub fields: HashMap<&'a str, &'a str>,
+    /// roles of the fields, see [`Role`]
+    pub roles: HashMap<&'a str, Role>,
+    /// primary key fields, composite keys have several fields
+    /// # Example
+    /// ```no_run
+    /// model.keys = vec![field!(oplog.pid)];
+    /// ```
+    pub keys: Vec<&'a str>,
+    /// delete only marks the data and queries skip the marked data
+    pub soft_delete: Option<SoftDelete<'a>>,
+    /// version field of optimistic locking, update checks and increases it
+    /// # Example
+    /// ```no_run
+    /// model.version = Some(field!(oplog.version));
+    /// ```
+    pub version: Option<&'a str>,
+    /// created and updated time fields
+    pub timestamps: Option<Timestamps<'a>>,
+    /// fields whose values are masked in errors, filter arguments compared to
+    /// them too, see [`Redaction`]
+    /// # Example
+    /// ```no_run
+    /// model.redact = vec![field!(user.password), field!(user.token)];
+    /// ```
+    pub redact: Vec<&'a str>,
+    pub(crate) middleware: Vec<&'a dyn Middleware>,
+    trashed: Trashed,
+    by_id: bool,
+    expect: Option<RowCount>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) retry: Option<Retry>,
 }
 
 impl<'a, T> Model<'a, T>
@@ -47,31 +573,604 @@ where
     T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
     &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
 {
+    /// model of an entity with the naming of its table and fields, the mappings
+    /// of `#[derive(Entity)]` are only applied by [`Model::of`]
     pub fn new(entity: &'a T) -> Model<'a, T> {
         let (name, fnames) = !entity;
+        let meta = meta_of(name, fnames);
 
-        // table name camel hump to underline
-        let mut table = String::new();
-        for ch in name.chars() {
-            if ch.is_uppercase() {
-                if table.len() > 0 {
-                    table.push('_');
-                }
-                table.push_str(&ch.to_lowercase().to_string());
-            } else {
-                table.push(ch);
+        Model {
+            entity,
+            meta,
+            table: meta.table.clone(),
+            fields: HashMap::new(),
+            roles: HashMap::new(),
+            keys: Vec::new(),
+            soft_delete: None,
+            version: None,
+            timestamps: None,
+            redact: Vec::new(),
+            middleware: Vec::new(),
+            trashed: Trashed::Exclude,
+            by_id: false,
+            expect: None,
+            timeout: None,
+            retry: None,
+        }
+    }
+
+    /// model of a derived entity with its declared mappings
+    /// # Example
+    /// ```no_run
+    /// let res = Model::of(&oplog).bind(&pool).insert_one(None).await?;
+    /// ```
+    pub fn of(entity: &'a T) -> Model<'a, T>
+    where
+        T: Entity,
+    {
+        let mut model = Model::new(entity);
+        if let Some(table) = T::TABLE {
+            model.table = table.to_string();
+        }
+        model.fields.extend(T::COLUMNS.iter().copied());
+        model.roles.extend(T::ROLES.iter().copied());
+        model.keys.extend(T::KEYS);
+        model.version = T::VERSION;
+        model.redact.extend(T::REDACT);
+        model
+    }
+
+    /// queries contain soft deleted data
+    pub fn with_deleted(mut self) -> Self {
+        self.trashed = Trashed::Include;
+        self
+    }
+
+    /// queries only contain soft deleted data
+    pub fn only_deleted(mut self) -> Self {
+        self.trashed = Trashed::Only;
+        self
+    }
+
+    /// update and delete must change exactly this number of rows, otherwise they are rolled back
+    pub fn expect_rows(mut self, rows: u64) -> Self {
+        self.expect = Some(RowCount::Exactly(rows));
+        self
+    }
+
+    /// update and delete must change no more than this number of rows, otherwise they are rolled back
+    pub fn expect_at_most(mut self, rows: u64) -> Self {
+        self.expect = Some(RowCount::AtMost(rows));
+        self
+    }
+
+    /// update and delete must change exactly one row, otherwise they are rolled back
+    pub fn expect_one(self) -> Self {
+        self.expect_rows(1)
+    }
+
+    /// run idempotent statements again on deadlocks and serialization failures:
+    /// queries, counts, deletes, and updates of a model without a version field,
+    /// statements on a connection inside a transaction are not retried
+    /// # Example
+    /// ```no_run
+    /// let res = Model::new(&oplog)
+    ///     .retry(Retry::new(5))
+    ///     .bind(&pool)
+    ///     .delete(&filter)
+    ///     .await?;
+    /// ```
+    pub fn retry(mut self, retry: Retry) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// retry policy of a statement, writes of a versioned model are not idempotent
+    pub(crate) fn retry_of(&self, write: bool) -> Option<Retry> {
+        self.retry.filter(|_| !write || self.version.is_none())
+    }
+
+    /// statements taking longer fail with [`Timeout`](crate::Timeout), the server
+    /// stops them where it supports a limit, otherwise the client drops them,
+    /// the limit is rounded up to whole milliseconds
+    ///
+    /// mssql has no server side limit and the dropped statement leaves the
+    /// client in the middle of a response, so the timeout has `discard` set and
+    /// the client must not be used again
+    /// # Example
+    /// ```no_run
+    /// let res = Model::new(&oplog)
+    ///     .timeout(Duration::from_secs(5))
+    ///     .bind(&pool)
+    ///     .query(&filter, None)
+    ///     .await?;
+    /// ```
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// deadline of a statement run with a client side timeout
+    pub(crate) fn deadline(&self) -> Deadline {
+        Deadline::new(self.timeout)
+    }
+
+    /// add a middleware seeing every statement of the model, in the order added
+    /// # Example
+    /// ```no_run
+    /// let res = Model::new(&oplog)
+    ///     .middleware(&tagging)
+    ///     .middleware(&tenant)
+    ///     .bind(&pool)
+    ///     .query(&filter, None)
+    ///     .await?;
+    /// ```
+    pub fn middleware(mut self, middleware: &'a dyn Middleware) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// run the middleware on a built statement of the invoked operation with a
+    /// number of bound arguments and open its trace, the sql may be rewritten and
+    /// the arguments added by the middleware are returned
+    pub(crate) fn trace(
+        &self,
+        kind: Kind,
+        soft: bool,
+        sql: &mut String,
+        args: &str,
+        params: usize,
+    ) -> Result<(Trace<'_>, Vec<Arg>)> {
+        let mut binds = Vec::new();
+        if !self.middleware.is_empty() {
+            let mut statement = Statement {
+                kind,
+                soft,
+                table: &self.table,
+                sql: std::mem::take(sql),
+                args,
+                params,
+                binds,
+            };
+            for middleware in &self.middleware {
+                middleware.before(&mut statement)?;
+            }
+            *sql = statement.sql;
+            binds = statement.binds;
+        }
+        let trace = Trace::start(
+            (kind, soft),
+            &self.table,
+            &self.middleware,
+            sql,
+            args,
+            params,
+            &binds,
+        );
+        Ok((trace, binds))
+    }
+
+    /// the value of the field at an index is masked in errors
+    pub(crate) fn redacted(&self, ix: usize) -> bool {
+        if self.redact.is_empty() {
+            return false;
+        }
+        let (_, fnames) = !self.entity;
+        fnames.get(ix).is_some_and(|fd| self.redact.contains(fd))
+    }
+
+    /// the filter argument placed after the sql compares a redacted field or
+    /// its column
+    pub(crate) fn redacted_filter(&self, sql: &str) -> bool {
+        if self.redact.is_empty() {
+            return false;
+        }
+        filter_field(sql).is_some_and(|name| {
+            self.redact
+                .iter()
+                .any(|&fd| fd == name || self.column(fd) == name)
+        })
+    }
+
+    /// expected number of changed rows
+    pub(crate) fn expected(&self) -> Option<RowCount> {
+        self.expect
+    }
+
+    /// role of a field, skipped when mapped to `-`
+    fn role(&self, fd: &str) -> Role {
+        if self.fields.get(fd) == Some(&"-") {
+            return Role::Skip;
+        }
+        self.roles.get(fd).copied().unwrap_or(Role::ReadWrite)
+    }
+
+    /// no column overrides and no roles, the cached column list applies
+    fn plain(&self) -> bool {
+        self.fields.is_empty() && self.roles.is_empty()
+    }
+
+    /// select list of the readable fields, `co as fd` for mapped columns
+    pub(crate) fn select_list(&self, q: Quote) -> Cow<'_, str> {
+        if self.plain() {
+            if let Some(list) = self.meta.columns(q) {
+                return Cow::Borrowed(list);
             }
         }
+        let mut list = String::new();
+        for &(_, fd) in self.meta.fields.iter().filter(|(_, fd)| self.readable(fd)) {
+            if !list.is_empty() {
+                list.push(',');
+            }
+            let co = self.column(fd);
+            list.push_str(&quote(co, q));
+            if co != fd {
+                list.push_str(" as ");
+                list.push_str(&quote(fd, q));
+            }
+        }
+        Cow::Owned(list)
+    }
 
-        let fields = fnames
+    /// column list of the fields written by insert
+    pub(crate) fn insert_list(&self, q: Quote) -> Cow<'_, str> {
+        if self.plain() {
+            if let Some(list) = self.meta.columns(q) {
+                return Cow::Borrowed(list);
+            }
+        }
+        let list = self
+            .insert_fields()
             .iter()
-            .map(|&f| (f, ""))
-            .collect::<HashMap<&'a str, &'a str>>();
+            .map(|&(_, fd)| quote(self.column(fd), q))
+            .collect::<Vec<_>>()
+            .join(",");
+        Cow::Owned(list)
+    }
 
-        Model {
-            entity,
-            table,
-            fields,
+    /// fields read by queries, (field index, field name)
+    pub(crate) fn read_fields(&self) -> Cow<'_, [(usize, &'static str)]> {
+        if self.plain() {
+            return Cow::Borrowed(&self.meta.fields);
+        }
+        let fields = self.meta.fields.iter();
+        Cow::Owned(
+            fields
+                .filter(|(_, fd)| self.readable(fd))
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// fields read back by returning and output clauses, only the columns of the
+    /// model table, joined columns and expressions are left out
+    #[cfg_attr(
+        not(any(feature = "postgres", feature = "sqlite", feature = "mssql")),
+        allow(dead_code)
+    )]
+    pub(crate) fn returned_fields(&self) -> Cow<'_, [(usize, &'static str)]> {
+        if self.plain() {
+            return Cow::Borrowed(&self.meta.fields);
+        }
+        let fields = self.meta.fields.iter();
+        Cow::Owned(
+            fields
+                .filter(|(_, fd)| self.returned(fd))
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// returning list of the returned fields, `co as fd` for mapped columns
+    #[cfg_attr(not(any(feature = "postgres", feature = "sqlite")), allow(dead_code))]
+    pub(crate) fn returning_list(&self, q: Quote) -> Cow<'_, str> {
+        if self.plain() {
+            if let Some(list) = self.meta.columns(q) {
+                return Cow::Borrowed(list);
+            }
+        }
+        let mut list = String::new();
+        for &(_, fd) in self.returned_fields().iter() {
+            if !list.is_empty() {
+                list.push(',');
+            }
+            let co = self.column(fd);
+            list.push_str(&quote(co, q));
+            if co != fd {
+                list.push_str(" as ");
+                list.push_str(&quote(fd, q));
+            }
+        }
+        Cow::Owned(list)
+    }
+
+    /// fields written by insert, (field index, field name)
+    pub(crate) fn insert_fields(&self) -> Cow<'_, [(usize, &'static str)]> {
+        if self.plain() {
+            return Cow::Borrowed(&self.meta.fields);
         }
+        let fields = self.meta.fields.iter();
+        Cow::Owned(
+            fields
+                .filter(|(_, fd)| self.insertable(fd))
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// the field is read by queries
+    pub(crate) fn readable(&self, fd: &str) -> bool {
+        matches!(
+            self.role(fd),
+            Role::ReadWrite | Role::ReadOnly | Role::NoUpdate
+        )
+    }
+
+    /// the field is read back from a column of the model table
+    pub(crate) fn returned(&self, fd: &'a str) -> bool {
+        self.readable(fd) && is_identifier(self.column(fd))
+    }
+
+    /// the field is written by insert
+    pub(crate) fn insertable(&self, fd: &str) -> bool {
+        matches!(
+            self.role(fd),
+            Role::ReadWrite | Role::WriteOnly | Role::InsertOnly | Role::NoUpdate
+        )
+    }
+
+    /// the field is written by update
+    pub(crate) fn updatable(&self, fd: &str) -> bool {
+        matches!(self.role(fd), Role::ReadWrite | Role::WriteOnly)
+    }
+
+    /// soft delete condition appended to the query filter
+    pub(crate) fn scope(&self, q: Quote) -> Option<String> {
+        let sd = self.soft_delete.as_ref()?;
+        match self.trashed {
+            Trashed::Exclude => Some(sd.condition(&self.table, false, q)),
+            Trashed::Include => None,
+            Trashed::Only => Some(sd.condition(&self.table, true, q)),
+        }
+    }
+
+    /// soft delete column and comparison of the query filter, quoted later by the dialect
+    pub(crate) fn scope_predicate(&self) -> Option<(&'a str, String)> {
+        let sd = self.soft_delete.as_ref()?;
+        match self.trashed {
+            Trashed::Exclude => Some((sd.column, sd.predicate(false))),
+            Trashed::Include => None,
+            Trashed::Only => Some((sd.column, sd.predicate(true))),
+        }
+    }
+
+    /// key columns of the entity, (field index, column name)
+    fn key_columns(&self) -> Result<Columns<'a>> {
+        if self.keys.is_empty() {
+            return Err(anyhow!("`{}` has no key fields", self.table).into());
+        }
+        let (_, fnames) = !self.entity;
+        let mut ids = Vec::new();
+        for key in &self.keys {
+            match fnames.iter().position(|fd| fd == key) {
+                Some(ix) => ids.push((ix, self.column(fnames[ix]))),
+                None => return Err(anyhow!("key field `{}` not found", key).into()),
+            }
+        }
+        Ok(ids)
+    }
+
+    /// key columns restricting the operations by id
+    pub(crate) fn id_columns(&self) -> Result<Columns<'a>> {
+        if self.by_id {
+            self.key_columns()
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// restrict the following operation to the key values of the entity
+    pub(crate) fn by_id(&mut self) {
+        self.by_id = true;
+    }
+
+    /// the key values of the entity are not assigned yet, zero numbers or empty strings
+    pub(crate) fn id_unset(&self) -> Result<bool> {
+        Ok(self
+            .key_columns()?
+            .iter()
+            .all(|(ix, _)| is_unset(&self.entity[*ix])))
+    }
+
+    /// skip the key fields so that the database generates them
+    pub(crate) fn skip_keys(&mut self) {
+        for &key in &self.keys {
+            self.fields.insert(key, "-");
+        }
+    }
+
+    /// value written to a field by insert or update
+    pub(crate) fn stamp(&self, fd: &str, insert: bool) -> Stamp<'a> {
+        match &self.timestamps {
+            Some(ts) if ts.updated == Some(fd) => Stamp::Clock(ts.clock),
+            Some(ts) if ts.created == Some(fd) => {
+                if insert {
+                    Stamp::Clock(ts.clock)
+                } else {
+                    Stamp::Skip
+                }
+            }
+            _ => Stamp::Entity,
+        }
+    }
+
+    /// updated time column of a batch update
+    pub(crate) fn updated_column(&self) -> Option<(&'a str, Clock<'a>)> {
+        let ts = self.timestamps.as_ref()?;
+        let (_, fnames) = !self.entity;
+        let fd = *fnames.iter().find(|&&fd| Some(fd) == ts.updated)?;
+        if self.updatable(fd) {
+            Some((self.column(fd), ts.clock))
+        } else {
+            None
+        }
+    }
+
+    /// database column of a field, skipped fields still have their own name
+    pub(crate) fn column(&self, fd: &'a str) -> &'a str {
+        column_of(&self.fields, fd)
+    }
+
+    /// version column of optimistic locking, (field index, column name)
+    pub(crate) fn version_column(&self) -> Result<Option<(usize, &'a str)>> {
+        let ver = match self.version {
+            Some(ver) => ver,
+            None => return Ok(None),
+        };
+        let (_, fnames) = !self.entity;
+        match fnames.iter().position(|&fd| fd == ver) {
+            Some(ix) => Ok(Some((ix, self.column(fnames[ix])))),
+            None => Err(anyhow!("version field `{}` not found", ver).into()),
+        }
+    }
+
+    /// key columns and update columns of a batch update, (field index, column name)
+    pub(crate) fn update_columns(&self, keys: &[&str]) -> Result<(Columns<'a>, Columns<'a>)> {
+        let (_, fnames) = !self.entity;
+        let version = self.version_column()?;
+
+        let mut kcols = Vec::new();
+        for key in keys {
+            match fnames.iter().position(|fd| fd == key) {
+                Some(ix) => kcols.push((ix, self.column(fnames[ix]))),
+                None => return Err(anyhow!("key field `{}` not found", key).into()),
+            }
+        }
+        if kcols.is_empty() {
+            return Err(anyhow!("at least one key field is required").into());
+        }
+
+        let mut vcols = Vec::new();
+        for (ix, &fd) in fnames.iter().enumerate() {
+            if kcols.iter().any(|(kx, _)| *kx == ix)
+                || !self.updatable(fd)
+                || matches!(version, Some((vx, _)) if vx == ix)
+                || !matches!(self.stamp(fd, false), Stamp::Entity)
+            {
+                continue;
+            }
+            vcols.push((ix, self.column(fd)));
+        }
+        if vcols.is_empty() {
+            return Err(anyhow!("no fields left to update besides the key fields").into());
+        }
+
+        Ok((kcols, vcols))
+    }
+}
+
+/// value equal to the default value of common key types
+fn is_unset(value: &dyn Any) -> bool {
+    fn eq<V: Default + PartialEq + 'static>(value: &dyn Any) -> Option<bool> {
+        value.downcast_ref::<V>().map(|p| *p == V::default())
+    }
+    eq::<i64>(value)
+        .or_else(|| eq::<i32>(value))
+        .or_else(|| eq::<i16>(value))
+        .or_else(|| eq::<i8>(value))
+        .or_else(|| eq::<u64>(value))
+        .or_else(|| eq::<u32>(value))
+        .or_else(|| eq::<u16>(value))
+        .or_else(|| eq::<u8>(value))
+        .or_else(|| eq::<String>(value))
+        .or_else(|| eq::<Option<i64>>(value))
+        .or_else(|| eq::<Option<i32>>(value))
+        .or_else(|| eq::<Option<String>>(value))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arg_errors() {
+        let err = Error::Conversion {
+            field: None,
+            ty: None,
+            source: None,
+            sql: None,
+        };
+        let err = arg_error("select * from oplog where pid=", err);
+        assert!(matches!(
+            &err,
+            Error::Conversion { sql: Some(sql), source: None, .. } if sql.ends_with("pid=")
+        ));
+
+        let err = arg_error("insert into oplog values(", anyhow!("no u32").into());
+        match &err {
+            Error::Conversion { sql, source, .. } => {
+                assert_eq!(sql.as_deref(), Some("insert into oplog values("));
+                assert_eq!(source.as_ref().unwrap().to_string(), "no u32");
+            }
+            _ => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn row_counts() {
+        assert!(check_rows("oplog", None, 3).is_ok());
+        assert!(check_rows("oplog", Some(RowCount::AtMost(3)), 3).is_ok());
+        let err = check_rows("oplog", Some(RowCount::Exactly(1)), 0).unwrap_err();
+        assert!(matches!(err, Error::RowCountMismatch(m) if m.actual == 0));
+    }
+
+    #[test]
+    fn unfiltered_writes() {
+        assert!(check_filter(&Filter::default(), "update").is_err());
+        let mut filter = Filter::default();
+        filter.and((" ", Vec::new()));
+        let err = check_filter(&filter, "delete").unwrap_err();
+        assert!(err.to_string().contains("use delete_all"));
+        filter.and(("pid = ?", vec![Box::new(1)]));
+        assert!(check_filter(&filter, "delete").is_ok());
+    }
+
+    const PG: Quote = ('"', '"', true);
+    const MSSQL: Quote = ('[', ']', false);
+
+    #[test]
+    fn identifiers() {
+        assert!(is_identifier("order"));
+        assert!(is_identifier("_user2"));
+        assert!(!is_identifier("2user"));
+        assert!(!is_identifier("student.name"));
+        assert!(!is_identifier("count(*)"));
+        assert!(!is_identifier(""));
+    }
+
+    #[test]
+    fn quotes() {
+        assert_eq!(quote("order", MSSQL), "[order]");
+        assert_eq!(quote("clazzName", MSSQL), "[clazzName]");
+        assert_eq!(quote("order", PG), "\"order\"");
+        assert_eq!(quote("clazzName", PG), "clazzName");
+        assert_eq!(quote("count(*)", PG), "count(*)");
+        assert_eq!(quote_table("audit.oplog", MSSQL), "[audit].[oplog]");
+        assert_eq!(quote_table("Audit.oplog", PG), "Audit.\"oplog\"");
+        assert_eq!(quote_table("audit oplog", PG), "audit oplog");
+    }
+
+    #[test]
+    fn quoted_exprs() {
+        assert_eq!(quote_expr("`order` = ?", MSSQL), "[order] = ?");
+        assert_eq!(quote_expr("`order` = ?", ('`', '`', false)), "`order` = ?");
+        assert_eq!(quote_expr("`clazzName` = ?", PG), "clazzName = ?");
+        assert_eq!(
+            quote_expr("`name` = '`a`' and `user` > ?", PG),
+            "\"name\" = '`a`' and \"user\" > ?"
+        );
+        assert_eq!(quote_expr("`my col` = ?", MSSQL), "[my col] = ?");
+        assert_eq!(quote_expr("name = ? and `pid", MSSQL), "name = ? and `pid");
     }
 }
@@ -1,14 +1,28 @@
 use std::{
     any::Any,
-    ops::{IndexMut, Not},
+    ops::{Deref, DerefMut, IndexMut, Not},
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use futures_util::{AsyncRead, AsyncWrite, StreamExt};
 use tiberius::{Client, Row, ToSql};
 
-use crate::{anyhow, Executor, Filter, LimitExecutor, OrderExecutor, Other, Result};
+use crate::{
+    anyhow, Error, Executor, Filter, LimitExecutor, OrderExecutor, Other, Result, Source,
+    StaleEntity,
+};
+
+use super::{
+    arg_error, batch_rows, check_filter, check_rows, quote, quote_expr, quote_table, shown,
+    sql_error, Affected, Arg, Backend, Batches, BoxFuture, Columns, Conv, Isolation, Kind, Model,
+    Quote, Retry, Stamp, Transact, Violation, ALL,
+};
+
+/// identifier quotes of the dialect
+const QUOTE: Quote = ('[', ']', false);
 
-use super::Model;
+/// bound parameters allowed in a statement
+const PARAMS: usize = 2100;
 
 pub trait Mssql<'a, T> {
     /// bind a database connection
@@ -119,206 +133,280 @@ where
     }
 }
 
-struct MssqlModel<'a, T, E, P, R>
+impl<T, S> Backend<T> for Client<S>
 where
-    E: AsyncRead + AsyncWrite + Unpin + Send,
-    P: Fn(&'a dyn Any, &mut Vec<&'a dyn ToSql>) -> Result<String> + Send,
-    R: Fn(&str, &Row, &mut dyn Any) -> Result<()> + Send,
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
 {
-    model: Model<'a, T>,
-    executor: &'a mut Client<E>,
-    to_arg: P,
-    from_row: R,
-    order: &'a str,
-    limit: &'a i64,
-    offset: &'a i64,
+    fn executor<'a>(&'a mut self, model: Model<'a, T>) -> impl Executor<'a, T>
+    where
+        T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+        &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    {
+        model.bind(self)
+    }
 }
 
-#[cfg_attr(feature = "async_trait", async_trait::async_trait)]
-impl<'a, T, E, P, R> Executor<'a, T> for MssqlModel<'a, T, E, P, R>
+/// owned client with conversion functions coerced from closures
+pub type MssqlConv<S> = Conv<
+    Client<S>,
+    for<'b> fn(&'b dyn Any, &mut Vec<&'b dyn ToSql>) -> Result<String>,
+    fn(&str, &Row, &mut dyn Any) -> Result<()>,
+>;
+
+impl<T, S, P, R> Backend<T> for Conv<Client<S>, P, R>
 where
-    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
-    &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: AsyncRead + AsyncWrite + Unpin + Send,
-    P: Fn(&'a dyn Any, &mut Vec<&'a dyn ToSql>) -> Result<String> + Send,
-    R: Fn(&str, &Row, &mut dyn Any) -> Result<()> + Send,
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    P: for<'b> Fn(&'b dyn Any, &mut Vec<&'b dyn ToSql>) -> Result<String> + Send + Sync + 'static,
+    R: Fn(&str, &Row, &mut dyn Any) -> Result<()> + Send + Sync + 'static,
 {
-    async fn insert_one(self, filter: Option<&'a Filter>) -> Result<u64> {
-        let (_, fnames) = !self.model.entity;
+    fn executor<'a>(&'a mut self, model: Model<'a, T>) -> impl Executor<'a, T>
+    where
+        T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+        &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    {
+        model.bind_conv(&mut self.backend, &self.to_arg, &self.from_row)
+    }
+}
 
-        let mut query = "insert into ".to_string();
-        query.push_str(&self.model.table);
+/// deadlock victim and snapshot update conflict
+pub(crate) fn retryable(err: &tiberius::error::Error) -> bool {
+    matches!(err.code(), Some(1205 | 3960))
+}
 
-        //fields section
-        let mut sep = false;
-        query.push('(');
-        for fd in fnames {
-            if let Some(&co) = self.model.fields.get(fd) {
-                if co.len() > 0 {
-                    if co != "-" {
-                        if sep {
-                            query.push(',');
-                        }
-                        query.push_str(co);
-                        sep = true;
-                    }
-                } else {
-                    if sep {
-                        query.push(',');
-                    }
-                    query.push_str(fd);
-                    sep = true;
-                }
-            }
+/// violated constraint of a server error, its name is quoted in the message
+pub(crate) fn violation(err: &tiberius::error::Error) -> Option<(Violation, Option<String>)> {
+    let token = match err {
+        tiberius::error::Error::Server(token) => token,
+        _ => return None,
+    };
+    let message = token.message();
+    match token.code() {
+        //Violation of PRIMARY KEY constraint 'PK_oplog'. Cannot insert duplicate key in object 'dbo.oplog'.
+        2627 => Some((Violation::Unique, quoted(message, '\'', 0))),
+        //Cannot insert duplicate key row in object 'dbo.oplog' with unique index 'IX_oplog'.
+        2601 => Some((Violation::Unique, quoted(message, '\'', 1))),
+        //The INSERT statement conflicted with the FOREIGN KEY constraint "FK_oplog".
+        547 if message.contains("FOREIGN KEY") || message.contains("REFERENCE") => {
+            Some((Violation::ForeignKey, quoted(message, '"', 0)))
         }
-        query.push(')');
+        547 => Some((Violation::Check, quoted(message, '"', 0))),
+        //Cannot insert the value NULL into column 'name', table 'school.dbo.oplog'
+        515 => Some((Violation::NotNull, quoted(message, '\'', 0))),
+        _ => None,
+    }
+}
 
-        let mut params: Vec<&dyn ToSql> = Vec::new();
-        let mut args = " ".to_string();
+/// name at a position among the quoted names of a message
+fn quoted(message: &str, quote: char, index: usize) -> Option<String> {
+    message.split(quote).nth(index * 2 + 1).map(str::to_string)
+}
 
-        //values section
-        sep = false;
-        if filter.is_some() {
-            query.push_str(" select ");
-        } else {
-            query.push_str(" values (");
-        }
-        for (ix, fd) in fnames.iter().enumerate() {
-            if let Some(&co) = self.model.fields.get(fd) {
-                if co == "-" {
-                    continue;
-                }
-            }
-            if sep {
-                query.push(',');
-            }
-            args.push_str(&(self.to_arg)(&self.model.entity[ix], &mut params)?);
-            args.push(' ');
-            query.push_str("@P");
-            query.push_str(&params.len().to_string());
-            sep = true;
-        }
+/// select of a page numbering the rows in their order, the columns are followed
+/// by the row number
+fn page_select(list: &str, order: &str) -> String {
+    let mut query = "select * from (select ".to_string();
+    query.push_str(list);
+    if !list.is_empty() {
+        query.push(',');
+    }
+    query.push_str("row_number() over (order by ");
+    if order.len() > 0 {
+        query.push_str(order);
+    } else {
+        query.push_str("(select 1)");
+    }
+    query.push_str(") as _num");
+    query
+}
 
-        //where statement section
-        if let Some(flt) = filter {
-            query.push_str(" where ");
+/// parameters of a statement followed by the arguments of its middleware
+fn bound<'p>(mut params: Vec<&'p dyn ToSql>, binds: &'p [Arg]) -> Vec<&'p dyn ToSql> {
+    params.extend(binds.iter().map(|bind| bind as &dyn ToSql));
+    params
+}
 
-            let mut idx = 0;
-            let mut quo = false;
-            for ch in flt.expr.chars() {
-                if ch == '\'' {
-                    quo = !quo;
-                }
-                if ch == '?' && !quo {
-                    if idx >= flt.args.len() {
-                        return Err(anyhow!(" ? exceeds the number of args"));
-                    }
-                    args.push_str(&(self.to_arg)(&*flt.args[idx], &mut params)?);
-                    args.push(' ');
-                    query.push_str("@P");
-                    query.push_str(&params.len().to_string());
-                    idx += 1;
-                } else {
-                    query.push(ch);
-                }
-            }
-            debug_assert!(!quo, "where statement quotation mark not closed error");
+/// open transaction or savepoint of a client, models are bound to `&mut **tx`
+/// and a savepoint is opened by `crudx::transaction(&mut **tx, ..)`
+pub struct MssqlTx<'t, S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    client: &'t mut Client<S>,
+    /// name of the savepoint, none for a transaction
+    savepoint: Option<String>,
+    isolation: bool,
+}
 
-            if idx != flt.args.len() {
-                return Err(anyhow!(
-                    "the quantity of ? is not equal to the number of parameters"
-                ));
-            }
-        } else {
-            query.push(')');
-        }
+/// number of the next savepoint, each one has its own name so that a nested
+/// savepoint does not hide the one of its parent
+static SAVEPOINTS: AtomicU64 = AtomicU64::new(0);
 
-        //execute sql statements
-        let res = match self.executor.execute(&query, &params).await {
-            Ok(res) => res.total(),
-            Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
-        };
+impl<S> Deref for MssqlTx<'_, S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    type Target = Client<S>;
 
-        Ok(res)
+    fn deref(&self) -> &Client<S> {
+        self.client
     }
+}
 
-    async fn insert(self, data: &'a [T]) -> Result<u64> {
-        let (_, fnames) = !self.model.entity;
-
-        let mut query = "insert into ".to_string();
-        query.push_str(&self.model.table);
+impl<S> DerefMut for MssqlTx<'_, S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    fn deref_mut(&mut self) -> &mut Client<S> {
+        self.client
+    }
+}
 
-        //fields section
-        let mut sep = false;
-        query.push('(');
-        for fd in fnames {
-            if let Some(&co) = self.model.fields.get(fd) {
-                if co.len() > 0 {
-                    if co != "-" {
-                        if sep {
-                            query.push(',');
-                        }
-                        query.push_str(co);
-                        sep = true;
-                    }
-                } else {
-                    if sep {
-                        query.push(',');
-                    }
-                    query.push_str(fd);
-                    sep = true;
-                }
-            }
+impl<S> MssqlTx<'_, S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    /// end the transaction, the isolation level of the session is restored
+    async fn end(self, sql: &str) -> Result<()> {
+        self.client.execute(sql, &[]).await?;
+        if self.isolation {
+            self.client
+                .execute("set transaction isolation level read committed", &[])
+                .await?;
         }
-        query.push_str(")  values ");
+        Ok(())
+    }
+}
 
-        let mut params: Vec<&dyn ToSql> = Vec::new();
-        let mut args = String::new();
+impl<S> Transact for Client<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    type Tx<'t>
+        = MssqlTx<'t, S>
+    where
+        S: 't;
+
+    fn begin(&mut self, isolation: Option<Isolation>) -> BoxFuture<'_, Result<Self::Tx<'_>>> {
+        Box::pin(async move {
+            let row = self
+                .query("select @@trancount", &[])
+                .await?
+                .into_row()
+                .await?;
+            let savepoint = match row.and_then(|r| r.get::<i32, _>(0)) {
+                Some(n) if n > 0 => Some(format!(
+                    "crudx_savepoint{}",
+                    SAVEPOINTS.fetch_add(1, Ordering::Relaxed)
+                )),
+                _ => None,
+            };
+            //savepoints keep the isolation level of their transaction
+            let isolation = isolation.filter(|_| savepoint.is_none());
+            let sql = match (&savepoint, isolation) {
+                (Some(name), _) => format!("save tran {}", name),
+                (None, Some(level)) => format!(
+                    "set transaction isolation level {}; begin tran",
+                    level.sql()
+                ),
+                (None, None) => "begin tran".to_string(),
+            };
+            self.execute(sql, &[]).await?;
+            Ok(MssqlTx {
+                client: self,
+                savepoint,
+                isolation: isolation.is_some(),
+            })
+        })
+    }
 
-        //values section
-        sep = false;
-        for row in data {
-            if sep {
-                query.push(',');
-                args.push(' ');
+    fn commit(tx: Self::Tx<'_>) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            if tx.savepoint.is_some() {
+                return Ok(());
             }
-            query.push('(');
-            args.push_str("[ ");
-            let mut sp = false;
-            for (ix, fd) in fnames.iter().enumerate() {
-                if let Some(&co) = self.model.fields.get(fd) {
-                    if co == "-" {
-                        continue;
-                    }
-                }
-                if sp {
-                    query.push(',');
-                }
-                args.push_str(&(self.to_arg)(&row[ix], &mut params)?);
-                args.push(' ');
-                query.push_str("@P");
-                query.push_str(&params.len().to_string());
-                sp = true;
+            tx.end("commit tran").await
+        })
+    }
+
+    fn rollback(tx: Self::Tx<'_>) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            if let Some(name) = &tx.savepoint {
+                tx.client
+                    .execute(format!("rollback tran {}", name), &[])
+                    .await?;
+                return Ok(());
             }
-            query.push(')');
-            args.push(']');
-            sep = true;
-        }
+            //an aborted transaction is already rolled back by the server
+            tx.end("if @@trancount > 0 rollback tran").await
+        })
+    }
+}
+
+struct MssqlModel<'a, T, E, P, R>
+where
+    E: AsyncRead + AsyncWrite + Unpin + Send,
+    P: Fn(&'a dyn Any, &mut Vec<&'a dyn ToSql>) -> Result<String> + Send,
+    R: Fn(&str, &Row, &mut dyn Any) -> Result<()> + Send,
+{
+    model: Model<'a, T>,
+    executor: &'a mut Client<E>,
+    to_arg: P,
+    from_row: R,
+    order: &'a str,
+    limit: &'a i64,
+    offset: &'a i64,
+}
+
+impl<'a, T, E, P, R> MssqlModel<'a, T, E, P, R>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+    &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    E: AsyncRead + AsyncWrite + Unpin + Send,
+    P: Fn(&'a dyn Any, &mut Vec<&'a dyn ToSql>) -> Result<String> + Send,
+    R: Fn(&str, &Row, &mut dyn Any) -> Result<()> + Send,
+{
+    /// update statement, the filter may be empty
+    async fn update_filter(&mut self, filter: &'a Filter) -> Result<u64> {
+        let (mut query, params, args) = self.update_statement(filter, false)?;
 
         //execute sql statements
-        let res = match self.executor.execute(&query, &params).await {
+        let (trace, binds) =
+            self.model
+                .trace(Kind::Update, false, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let res = match timed!(
+            self.model.deadline(),
+            trace,
+            self.executor.execute(&query, &params),
+            &query,
+            discard
+        ) {
             Ok(res) => res.total(),
-            Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
         };
+        trace.rows(res);
+
+        if self.model.version.is_some() && res == 0 {
+            return Err(Error::StaleEntity(StaleEntity {
+                table: self.model.table.clone(),
+            }));
+        }
 
         Ok(res)
     }
 
-    async fn update(self, filter: &'a Filter) -> Result<u64> {
+    /// build the update statement, output the updated data if needed
+    fn update_statement(
+        &self,
+        filter: &'a Filter,
+        output: bool,
+    ) -> Result<(String, Vec<&'a dyn ToSql>, String)> {
         let (_, fnames) = !self.model.entity;
+        let version = self.model.version_column()?;
+        let ids = self.model.id_columns()?;
 
         let mut query = "update ".to_string();
-        query.push_str(&self.model.table);
+        query.push_str(&quote_table(&self.model.table, QUOTE));
         query.push_str(" set ");
 
         let mut params: Vec<&dyn ToSql> = Vec::new();
@@ -327,51 +415,72 @@ where
         //update statement section
         let mut sep = false;
         for (ix, fd) in fnames.iter().enumerate() {
-            if let Some(&co) = self.model.fields.get(fd) {
-                if co.len() > 0 {
-                    if co != "-" {
-                        if sep {
-                            query.push(',');
-                        }
-                        query.push_str(co);
-                        query.push('=');
-                        args.push_str(&(self.to_arg)(&self.model.entity[ix], &mut params)?);
-                        args.push(' ');
-                        query.push_str("@P");
-                        query.push_str(&params.len().to_string());
-                        sep = true;
-                    }
-                } else {
-                    if sep {
-                        query.push(',');
-                    }
-                    query.push_str(fd);
-                    query.push('=');
-                    args.push_str(&(self.to_arg)(&self.model.entity[ix], &mut params)?);
-                    args.push(' ');
-                    query.push_str("@P");
-                    query.push_str(&params.len().to_string());
-                    sep = true;
-                }
+            if !self.model.updatable(fd) {
+                continue;
+            }
+            let stamp = self.model.stamp(fd, false);
+            if matches!(version, Some((vx, _)) if vx == ix)
+                || matches!(stamp, Stamp::Skip)
+                || ids.iter().any(|(kx, _)| *kx == ix)
+            {
+                continue;
+            }
+            if sep {
+                query.push(',');
+            }
+            query.push_str(&quote(self.model.column(fd), QUOTE));
+            query.push('=');
+            mssql_push_value!(
+                self,
+                query,
+                params,
+                args,
+                &self.model.entity[ix],
+                self.model.redacted(ix),
+                stamp
+            );
+            sep = true;
+        }
+
+        //version statement section
+        if let Some((_, vc)) = version {
+            if sep {
+                query.push(',');
             }
+            query.push_str(&quote(vc, QUOTE));
+            query.push('=');
+            query.push_str(&quote(vc, QUOTE));
+            query.push_str("+1");
+        }
+
+        //output statement section
+        if output {
+            query.push_str(&self.output_columns("inserted"));
         }
 
         let mut idx = 0;
 
         //where statement section
+        let conds = ids.iter().chain(version.iter()).collect::<Vec<_>>();
         if filter.expr.len() > 0 {
             query.push_str(" where ");
+            if !conds.is_empty() {
+                query.push('(');
+            }
 
             let mut quo = false;
-            for ch in filter.expr.chars() {
+            for ch in quote_expr(&filter.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= filter.args.len() {
-                        return Err(anyhow!(" ? exceeds the number of args"));
+                        return Err(Error::ArgCountMismatch);
                     }
-                    args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                    let field = self.model.redacted_filter(&query);
+                    let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                        .map_err(|err| arg_error(&query, err))?;
+                    args.push_str(&shown(arg, &*filter.args[idx], field));
                     args.push(' ');
                     query.push_str("@P");
                     query.push_str(&params.len().to_string());
@@ -381,27 +490,89 @@ where
                 }
             }
             debug_assert!(!quo, "where statement quotation mark not closed error");
+            if !conds.is_empty() {
+                query.push(')');
+            }
         }
+        mssql_push_conditions!(
+            self,
+            query,
+            params,
+            args,
+            None::<String>,
+            conds,
+            !filter.expr.is_empty()
+        );
 
         if idx != filter.args.len() {
-            return Err(anyhow!(
-                "the quantity of ? is not equal to the number of parameters"
-            ));
+            return Err(Error::ArgCountMismatch);
         }
 
+        Ok((query, params, args))
+    }
+
+    /// delete statement, the filter may be empty, soft delete is skipped by hard
+    async fn delete_filter(&mut self, filter: &'a Filter, hard: bool) -> Result<u64> {
+        let (mut query, params, args) = self.delete_statement(filter, hard, false)?;
+        let soft = !hard && self.model.soft_delete.is_some();
+
         //execute sql statements
-        let res = match self.executor.execute(&query, &params).await {
+        let (trace, binds) =
+            self.model
+                .trace(Kind::Delete, soft, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let res = match timed!(
+            self.model.deadline(),
+            trace,
+            self.executor.execute(&query, &params),
+            &query,
+            discard
+        ) {
             Ok(res) => res.total(),
-            Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
         };
+        trace.rows(res);
 
         Ok(res)
     }
 
-    async fn delete(self, filter: &'a Filter) -> Result<u64> {
+    /// build the delete statement, output the deleted data if needed
+    fn delete_statement(
+        &self,
+        filter: &'a Filter,
+        hard: bool,
+        output: bool,
+    ) -> Result<(String, Vec<&'a dyn ToSql>, String)> {
+        //soft delete updates the deleted mark instead
+        let soft = if hard {
+            None
+        } else {
+            self.model.soft_delete.as_ref()
+        };
+        let scope = soft.map(|sd| sd.condition(&self.model.table, false, QUOTE));
+        let ids = self.model.id_columns()?;
+        let more = scope.is_some() || !ids.is_empty();
+
         //from statement section
-        let mut query = "delete from ".to_string();
-        query.push_str(&self.model.table);
+        let mut query = if let Some(sd) = soft {
+            let mut query = "update ".to_string();
+            query.push_str(&quote_table(&self.model.table, QUOTE));
+            query.push_str(" set ");
+            query.push_str(&quote(sd.column, QUOTE));
+            query.push('=');
+            query.push_str(sd.deleted);
+            if output {
+                query.push_str(&self.output_columns("inserted"));
+            }
+            query
+        } else {
+            let mut query = "delete from ".to_string();
+            query.push_str(&quote_table(&self.model.table, QUOTE));
+            if output {
+                query.push_str(&self.output_columns("deleted"));
+            }
+            query
+        };
 
         let mut params: Vec<&dyn ToSql> = Vec::new();
         let mut args = " ".to_string();
@@ -410,17 +581,23 @@ where
         //where statement section
         if filter.expr.len() > 0 {
             query.push_str(" where ");
+            if more {
+                query.push('(');
+            }
 
             let mut quo = false;
-            for ch in filter.expr.chars() {
+            for ch in quote_expr(&filter.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= filter.args.len() {
-                        return Err(anyhow!(" ? exceeds the number of args"));
+                        return Err(Error::ArgCountMismatch);
                     }
-                    args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                    let field = self.model.redacted_filter(&query);
+                    let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                        .map_err(|err| arg_error(&query, err))?;
+                    args.push_str(&shown(arg, &*filter.args[idx], field));
                     args.push(' ');
                     query.push_str("@P");
                     query.push_str(&params.len().to_string());
@@ -430,54 +607,388 @@ where
                 }
             }
             debug_assert!(!quo, "where statement quotation mark not closed error");
+            if more {
+                query.push(')');
+            }
         }
+        mssql_push_conditions!(
+            self,
+            query,
+            params,
+            args,
+            scope,
+            ids,
+            !filter.expr.is_empty()
+        );
 
         if idx != filter.args.len() {
-            return Err(anyhow!(
-                "the quantity of ? is not equal to the number of parameters"
-            ));
+            return Err(Error::ArgCountMismatch);
         }
 
-        //execute sql statements
-        let res = match self.executor.execute(&query, &params).await {
-            Ok(res) => res.total(),
-            Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
-        };
+        Ok((query, params, args))
+    }
 
+    /// update statement with output clause
+    async fn update_output(&mut self, filter: &'a Filter) -> Result<Vec<T>> {
+        let (query, params, args) = self.update_statement(filter, true)?;
+        let res = self
+            .fetch_output(query, params, args, Kind::Update, false)
+            .await?;
+        if self.model.version.is_some() && res.is_empty() {
+            return Err(Error::StaleEntity(StaleEntity {
+                table: self.model.table.clone(),
+            }));
+        }
         Ok(res)
     }
 
-    async fn count(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<i64> {
-        //determine if there is group by
-        let mut query = if matches!(&other,Some(ot) if ot.group_by.len() > 0) {
-            "select count(*) from (select 1 as n".to_string()
-        } else {
-            "select count(*)".to_string()
-        };
+    /// delete statement with output clause
+    async fn delete_output(&mut self, filter: &'a Filter) -> Result<Vec<T>> {
+        let (query, params, args) = self.delete_statement(filter, false, true)?;
+        let soft = self.model.soft_delete.is_some();
+        self.fetch_output(query, params, args, Kind::Delete, soft)
+            .await
+    }
 
-        //from statement section
-        query.push_str(" from ");
-        query.push_str(&self.model.table);
+    /// update some data by key with merge statements, data over the parameter
+    /// limit is split into batches run in a transaction
+    async fn merge_rows(&mut self, data: &'a [T], keys: &'a [&'a str]) -> Result<Batches> {
+        let mut counts = Vec::new();
+        if data.is_empty() {
+            return Ok(Batches(counts));
+        }
+        let (kcols, vcols) = self.model.update_columns(keys)?;
+        let version = self.model.version_column()?;
+        let cols = kcols
+            .iter()
+            .chain(vcols.iter())
+            .chain(version.iter())
+            .collect::<Vec<_>>();
 
-        let mut params: Vec<&dyn ToSql> = Vec::new();
-        let mut args = " ".to_string();
-        let mut idx = 0;
-        let mut quo = false;
+        let size = batch_rows(PARAMS, cols.len());
+        if data.len() <= size {
+            counts.push(
+                self.merge_batch(data, &kcols, &vcols, version, &cols)
+                    .await?,
+            );
+            return Ok(Batches(counts));
+        }
+        let nested = self.tran_begin().await?;
+        let mut res = Ok(());
+        for rows in data.chunks(size) {
+            match self.merge_batch(rows, &kcols, &vcols, version, &cols).await {
+                Ok(count) => counts.push(count),
+                Err(err) => {
+                    res = Err(err);
+                    break;
+                }
+            }
+        }
+        self.tran_end(nested, res.map(|_| Batches(counts))).await
+    }
 
-        //join on statement section
-        if let Some(ot) = &other {
-            if ot.join_on.len() > 0 {
-                query.push(' ');
+    /// merge statement of a batch of rows
+    async fn merge_batch(
+        &mut self,
+        data: &'a [T],
+        kcols: &Columns<'a>,
+        vcols: &Columns<'a>,
+        version: Option<(usize, &'a str)>,
+        cols: &[&(usize, &'a str)],
+    ) -> Result<u64> {
+        let mut query = "merge into ".to_string();
+        query.push_str(&quote_table(&self.model.table, QUOTE));
 
-                for ch in ot.join_on.chars() {
-                    if ch == '\'' {
-                        quo = !quo;
-                    }
-                    if ch == '?' && !quo {
-                        if idx >= filter.args.len() {
-                            return Err(anyhow!(" ? exceeds the number of args"));
+        let mut params: Vec<&dyn ToSql> = Vec::new();
+        let mut args = String::new();
+
+        //values section
+        query.push_str(" using (values ");
+        for (n, row) in data.iter().enumerate() {
+            if n > 0 {
+                query.push(',');
+                args.push(' ');
+            }
+            query.push('(');
+            args.push_str("[ ");
+            for (m, (ix, _)) in cols.iter().enumerate() {
+                if m > 0 {
+                    query.push(',');
+                }
+                let arg =
+                    (self.to_arg)(&row[*ix], &mut params).map_err(|err| arg_error(&query, err))?;
+                args.push_str(&shown(arg, &row[*ix], self.model.redacted(*ix)));
+                args.push(' ');
+                query.push_str("@P");
+                query.push_str(&params.len().to_string());
+            }
+            query.push(')');
+            args.push(']');
+        }
+        query.push_str(") as v(");
+        for n in 0..cols.len() {
+            if n > 0 {
+                query.push(',');
+            }
+            query.push('c');
+            query.push_str(&n.to_string());
+        }
+        query.push(')');
+
+        //on statement section
+        query.push_str(" on ");
+        for (n, (_, co)) in kcols.iter().chain(version.iter()).enumerate() {
+            if n > 0 {
+                query.push_str(" and ");
+            }
+            query.push_str(&quote_table(&self.model.table, QUOTE));
+            query.push('.');
+            query.push_str(&quote(co, QUOTE));
+            query.push_str("=v.c");
+            if n < kcols.len() {
+                query.push_str(&n.to_string());
+            } else {
+                query.push_str(&(kcols.len() + vcols.len()).to_string());
+            }
+        }
+
+        //update statement section
+        query.push_str(" when matched then update set ");
+        for (n, (_, co)) in vcols.iter().enumerate() {
+            if n > 0 {
+                query.push(',');
+            }
+            query.push_str(&quote(co, QUOTE));
+            query.push_str("=v.c");
+            query.push_str(&(kcols.len() + n).to_string());
+        }
+
+        //version statement section
+        if let Some((_, vc)) = version {
+            query.push(',');
+            query.push_str(&quote(vc, QUOTE));
+            query.push('=');
+            query.push_str(&quote(vc, QUOTE));
+            query.push_str("+1");
+        }
+
+        //updated time statement section
+        if let Some((uc, clock)) = self.model.updated_column() {
+            query.push(',');
+            query.push_str(&quote(uc, QUOTE));
+            query.push('=');
+            mssql_push_value!(self, query, params, args, &(), false, Stamp::Clock(clock));
+        }
+        query.push(';');
+
+        //execute sql statements
+        let (trace, binds) =
+            self.model
+                .trace(Kind::Update, false, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let res = match timed!(
+            self.model.deadline(),
+            trace,
+            self.executor.execute(&query, &params),
+            &query,
+            discard
+        ) {
+            Ok(res) => res.total(),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
+        };
+        trace.rows(res);
+
+        if version.is_some() && res < data.len() as u64 {
+            return Err(Error::StaleEntity(StaleEntity {
+                table: self.model.table.clone(),
+            }));
+        }
+
+        Ok(res)
+    }
+
+    /// whether the connection is in a transaction
+    async fn in_tran(&mut self) -> Result<bool> {
+        let row = self
+            .executor
+            .query("select @@trancount", &[])
+            .await?
+            .into_row()
+            .await?;
+        Ok(matches!(row.and_then(|r| r.get::<i32, _>(0)), Some(n) if n > 0))
+    }
+
+    /// retry policy of the statement, statements in a transaction are not retried
+    async fn retry_of(&mut self, write: bool) -> Result<Option<Retry>> {
+        match self.model.retry_of(write) {
+            Some(retry) if !self.in_tran().await? => Ok(Some(retry)),
+            _ => Ok(None),
+        }
+    }
+
+    /// begin a transaction, or a savepoint when one is active, true for a savepoint
+    async fn tran_begin(&mut self) -> Result<bool> {
+        let nested = self.in_tran().await?;
+        let begin = if nested {
+            "save tran crudx_expect"
+        } else {
+            "begin tran"
+        };
+        self.executor.execute(begin, &[]).await?;
+        Ok(nested)
+    }
+
+    /// commit the transaction of tran_begin, it is rolled back on error
+    async fn tran_end<V>(&mut self, nested: bool, res: Result<V>) -> Result<V> {
+        match res {
+            Ok(v) => {
+                if !nested {
+                    self.executor.execute("commit tran", &[]).await?;
+                }
+                Ok(v)
+            }
+            Err(err) => {
+                //a doomed transaction can not return to its savepoint and one ended
+                //by a deadlock is gone, the original error is kept for the retries
+                let rollback = if nested {
+                    "if xact_state() = 1 rollback tran crudx_expect"
+                } else {
+                    "if @@trancount > 0 rollback tran"
+                };
+                let _ = self.executor.execute(rollback, &[]).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// an expected row count runs the write in a transaction, or in a savepoint
+    /// when one is active, returns whether it is a savepoint
+    async fn expect_begin(&mut self) -> Result<Option<bool>> {
+        if self.model.expected().is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.tran_begin().await?))
+    }
+
+    /// check the changed rows, the write is rolled back on error or unexpected rows
+    async fn expect_end<V: Affected>(&mut self, tran: Option<bool>, res: Result<V>) -> Result<V> {
+        let nested = match tran {
+            Some(nested) => nested,
+            None => return res,
+        };
+        let res = res.and_then(|v| {
+            check_rows(&self.model.table, self.model.expected(), v.affected())?;
+            Ok(v)
+        });
+        self.tran_end(nested, res).await
+    }
+
+    /// output clause of the inserted or deleted pseudo table
+    fn output_columns(&self, pseudo: &str) -> String {
+        let (_, fnames) = !self.model.entity;
+        let mut output = " output ".to_string();
+        let mut sep = false;
+        for fd in fnames {
+            if !self.model.returned(fd) {
+                continue;
+            }
+            let co = self.model.column(fd);
+            if sep {
+                output.push(',');
+            }
+            output.push_str(pseudo);
+            output.push('.');
+            output.push_str(&quote(co, QUOTE));
+            output.push_str(" as ");
+            output.push_str(&quote(fd, QUOTE));
+            sep = true;
+        }
+        output
+    }
+
+    /// execute the statement with output clause and read the data
+    async fn fetch_output(
+        &mut self,
+        mut query: String,
+        params: Vec<&'a dyn ToSql>,
+        args: String,
+        kind: Kind,
+        soft: bool,
+    ) -> Result<Vec<T>> {
+        //query column section
+        let fds = self.model.returned_fields();
+
+        //execute sql statements
+        let mut res = Vec::new();
+        let deadline = self.model.deadline();
+        let (trace, binds) = self
+            .model
+            .trace(kind, soft, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let mut stream = match timed!(
+            deadline,
+            trace,
+            self.executor.query(&query, &params),
+            &query,
+            discard
+        ) {
+            Ok(stream) => stream,
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
+        };
+        while let Some(rst) = timed!(deadline, trace, stream.next(), &query, discard) {
+            match rst {
+                Ok(item) => {
+                    if let Some(row) = item.as_row() {
+                        let mut entity = self.model.entity.clone();
+                        for (ix, fd) in fds.iter() {
+                            (self.from_row)(fd, row, &mut entity[*ix])?;
                         }
-                        args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                        res.push(entity);
+                    }
+                }
+                Err(err) => return Err(trace.fail(sql_error(query, args, err))),
+            }
+        }
+        trace.rows(res.len() as u64);
+
+        Ok(res)
+    }
+
+    /// count statement of the filter
+    async fn count_filter(&mut self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<i64> {
+        //determine if there is group by
+        let mut query = if matches!(&other,Some(ot) if ot.group_by.len() > 0) {
+            "select count(*) from (select 1 as n".to_string()
+        } else {
+            "select count(*)".to_string()
+        };
+
+        //from statement section
+        query.push_str(" from ");
+        query.push_str(&quote_table(&self.model.table, QUOTE));
+
+        let mut params: Vec<&dyn ToSql> = Vec::new();
+        let mut args = " ".to_string();
+        let mut idx = 0;
+        let mut quo = false;
+
+        //join on statement section
+        if let Some(ot) = &other {
+            if ot.join_on.len() > 0 {
+                query.push(' ');
+
+                for ch in ot.join_on.chars() {
+                    if ch == '\'' {
+                        quo = !quo;
+                    }
+                    if ch == '?' && !quo {
+                        if idx >= filter.args.len() {
+                            return Err(Error::ArgCountMismatch);
+                        }
+                        let field = self.model.redacted_filter(&query);
+                        let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                            .map_err(|err| arg_error(&query, err))?;
+                        args.push_str(&shown(arg, &*filter.args[idx], field));
                         args.push(' ');
                         query.push_str("@P");
                         query.push_str(&params.len().to_string());
@@ -491,18 +1002,27 @@ where
         debug_assert!(!quo, "join on statement quotation mark not closed error");
 
         //where statement section
+        let scope = self.model.scope(QUOTE);
+        let ids = self.model.id_columns()?;
+        let more = scope.is_some() || !ids.is_empty();
         if filter.expr.len() > 0 {
             query.push_str(" where ");
+            if more {
+                query.push('(');
+            }
 
-            for ch in filter.expr.chars() {
+            for ch in quote_expr(&filter.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= filter.args.len() {
-                        return Err(anyhow!(" ? exceeds the number of args"));
+                        return Err(Error::ArgCountMismatch);
                     }
-                    args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                    let field = self.model.redacted_filter(&query);
+                    let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                        .map_err(|err| arg_error(&query, err))?;
+                    args.push_str(&shown(arg, &*filter.args[idx], field));
                     args.push(' ');
                     query.push_str("@P");
                     query.push_str(&params.len().to_string());
@@ -511,7 +1031,19 @@ where
                     query.push(ch);
                 }
             }
+            if more {
+                query.push(')');
+            }
         }
+        mssql_push_conditions!(
+            self,
+            query,
+            params,
+            args,
+            scope,
+            ids,
+            !filter.expr.is_empty()
+        );
         debug_assert!(!quo, "where statement quotation mark not closed error");
 
         //group by statement section
@@ -530,9 +1062,12 @@ where
                         }
                         if ch == '?' && !quo {
                             if idx >= filter.args.len() {
-                                return Err(anyhow!(" ? exceeds the number of args"));
+                                return Err(Error::ArgCountMismatch);
                             }
-                            args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                            let field = self.model.redacted_filter(&query);
+                            let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                                .map_err(|err| arg_error(&query, err))?;
+                            args.push_str(&shown(arg, &*filter.args[idx], field));
                             args.push(' ');
                             query.push_str("@P");
                             query.push_str(&params.len().to_string());
@@ -549,78 +1084,55 @@ where
         debug_assert!(!quo, "having statement quotation mark not closed error");
 
         if idx != filter.args.len() {
-            return Err(anyhow!(
-                "the quantity of ? is not equal to the number of parameters"
-            ));
+            return Err(Error::ArgCountMismatch);
         }
 
         //execute sql statements
-        let stream = match self.executor.query(&query, &params).await {
+        let deadline = self.model.deadline();
+        let (trace, binds) =
+            self.model
+                .trace(Kind::Select, false, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let stream = match timed!(
+            deadline,
+            trace,
+            self.executor.query(&query, &params),
+            &query,
+            discard
+        ) {
             Ok(stream) => stream,
-            Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
         };
-        let row = match stream.into_row().await {
+        let row = match timed!(deadline, trace, stream.into_row(), &query, discard) {
             Ok(row) => row,
-            Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
         };
+        trace.rows(row.is_some() as u64);
 
         let res: Option<i32> = match row {
             Some(row) => row.try_get(0)?,
-            None => return Err(anyhow!("sql:`{}` args:[{}]  result is None", query, args)),
+            None => return Err(anyhow!("sql:`{}` args:[{}]  result is None", query, args).into()),
         };
         match res {
             Some(res) => Ok(res as i64),
-            None => Err(anyhow!("sql:`{}` args:[{}]  result is None", query, args)),
+            None => Err(anyhow!("sql:`{}` args:[{}]  result is None", query, args).into()),
         }
     }
 
-    fn order_by(mut self, order: &'a str) -> impl OrderExecutor<'a, T> {
-        self.order = order;
-        self
-    }
-}
-
-#[cfg_attr(feature = "async_trait", async_trait::async_trait)]
-impl<'a, T, E, P, R> OrderExecutor<'a, T> for MssqlModel<'a, T, E, P, R>
-where
-    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
-    &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: AsyncRead + AsyncWrite + Unpin + Send,
-    P: Fn(&'a dyn Any, &mut Vec<&'a dyn ToSql>) -> Result<String> + Send,
-    R: Fn(&str, &Row, &mut dyn Any) -> Result<()> + Send,
-{
-    async fn query_one(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<T> {
-        let (_, fnames) = !self.model.entity;
-
+    /// query statement of the first row of the filter
+    async fn query_one_filter(
+        &mut self,
+        filter: &'a Filter,
+        other: Option<Other<'a>>,
+    ) -> Result<T> {
         let mut query = "select top 1 ".to_string();
 
         //select statement section
-        let mut sep = false;
-        for fd in fnames {
-            if let Some(&co) = self.model.fields.get(fd) {
-                if co.len() > 0 {
-                    if co != "-" {
-                        if sep {
-                            query.push(',');
-                        }
-                        query.push_str(co);
-                        query.push_str(" as ");
-                        query.push_str(fd);
-                        sep = true;
-                    }
-                } else {
-                    if sep {
-                        query.push(',');
-                    }
-                    query.push_str(fd);
-                    sep = true;
-                }
-            }
-        }
+        query.push_str(&self.model.select_list(QUOTE));
 
         //from statement section
         query.push_str(" from ");
-        query.push_str(&self.model.table);
+        query.push_str(&quote_table(&self.model.table, QUOTE));
 
         let mut params: Vec<&dyn ToSql> = Vec::new();
         let mut args = " ".to_string();
@@ -638,9 +1150,12 @@ where
                     }
                     if ch == '?' && !quo {
                         if idx >= filter.args.len() {
-                            return Err(anyhow!(" ? exceeds the number of args"));
+                            return Err(Error::ArgCountMismatch);
                         }
-                        args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                        let field = self.model.redacted_filter(&query);
+                        let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                            .map_err(|err| arg_error(&query, err))?;
+                        args.push_str(&shown(arg, &*filter.args[idx], field));
                         args.push(' ');
                         query.push_str("@P");
                         query.push_str(&params.len().to_string());
@@ -654,18 +1169,27 @@ where
         debug_assert!(!quo, "join on statement quotation mark not closed error");
 
         //where statement section
+        let scope = self.model.scope(QUOTE);
+        let ids = self.model.id_columns()?;
+        let more = scope.is_some() || !ids.is_empty();
         if filter.expr.len() > 0 {
             query.push_str(" where ");
+            if more {
+                query.push('(');
+            }
 
-            for ch in filter.expr.chars() {
+            for ch in quote_expr(&filter.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= filter.args.len() {
-                        return Err(anyhow!(" ? exceeds the number of args"));
+                        return Err(Error::ArgCountMismatch);
                     }
-                    args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                    let field = self.model.redacted_filter(&query);
+                    let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                        .map_err(|err| arg_error(&query, err))?;
+                    args.push_str(&shown(arg, &*filter.args[idx], field));
                     args.push(' ');
                     query.push_str("@P");
                     query.push_str(&params.len().to_string());
@@ -674,7 +1198,19 @@ where
                     query.push(ch);
                 }
             }
+            if more {
+                query.push(')');
+            }
         }
+        mssql_push_conditions!(
+            self,
+            query,
+            params,
+            args,
+            scope,
+            ids,
+            !filter.expr.is_empty()
+        );
         debug_assert!(!quo, "where statement quotation mark not closed error");
 
         //group by statement section
@@ -693,9 +1229,12 @@ where
                         }
                         if ch == '?' && !quo {
                             if idx >= filter.args.len() {
-                                return Err(anyhow!(" ? exceeds the number of args"));
+                                return Err(Error::ArgCountMismatch);
                             }
-                            args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                            let field = self.model.redacted_filter(&query);
+                            let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                                .map_err(|err| arg_error(&query, err))?;
+                            args.push_str(&shown(arg, &*filter.args[idx], field));
                             args.push(' ');
                             query.push_str("@P");
                             query.push_str(&params.len().to_string());
@@ -710,9 +1249,7 @@ where
         debug_assert!(!quo, "having statement quotation mark not closed error");
 
         if idx != filter.args.len() {
-            return Err(anyhow!(
-                "the quantity of ? is not equal to the number of parameters"
-            ));
+            return Err(Error::ArgCountMismatch);
         }
 
         //order by statement section
@@ -722,65 +1259,54 @@ where
         }
 
         //execute sql statements
-        let stream = match self.executor.query(&query, &params).await {
+        let deadline = self.model.deadline();
+        let (trace, binds) =
+            self.model
+                .trace(Kind::Select, false, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let stream = match timed!(
+            deadline,
+            trace,
+            self.executor.query(&query, &params),
+            &query,
+            discard
+        ) {
             Ok(stream) => stream,
-            Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
         };
-        let row = match stream.into_row().await {
+        let row = match timed!(deadline, trace, stream.into_row(), &query, discard) {
             Ok(row) => row,
-            Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
         };
+        trace.rows(row.is_some() as u64);
         let row = match row {
             Some(row) => row,
-            None => return Err(anyhow!("sql:`{}` args:[{}]  result is None", query, args)),
+            None => return Err(Error::NotFound),
         };
 
         //convert data rows to entities
         let mut res = self.model.entity.clone();
-        for (ix, fd) in fnames.iter().enumerate() {
-            if let Some(&co) = self.model.fields.get(fd) {
-                if co == "-" {
-                    continue;
-                }
-            }
+        for &(ix, fd) in self.model.read_fields().iter() {
             (self.from_row)(fd, &row, &mut res[ix])?;
         }
 
         Ok(res)
     }
 
-    async fn query(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<Vec<T>> {
-        let (_, fnames) = !self.model.entity;
-
+    /// query statement of the filter
+    async fn query_filter(
+        &mut self,
+        filter: &'a Filter,
+        other: Option<Other<'a>>,
+    ) -> Result<Vec<T>> {
         let mut query = "select ".to_string();
 
         //select statement section
-        let mut sep = false;
-        for fd in fnames {
-            if let Some(&co) = self.model.fields.get(fd) {
-                if co.len() > 0 {
-                    if co != "-" {
-                        if sep {
-                            query.push(',');
-                        }
-                        query.push_str(co);
-                        query.push_str(" as ");
-                        query.push_str(fd);
-                        sep = true;
-                    }
-                } else {
-                    if sep {
-                        query.push(',');
-                    }
-                    query.push_str(fd);
-                    sep = true;
-                }
-            }
-        }
+        query.push_str(&self.model.select_list(QUOTE));
 
         //from statement section
         query.push_str(" from ");
-        query.push_str(&self.model.table);
+        query.push_str(&quote_table(&self.model.table, QUOTE));
 
         let mut params: Vec<&dyn ToSql> = Vec::new();
         let mut args = " ".to_string();
@@ -798,9 +1324,12 @@ where
                     }
                     if ch == '?' && !quo {
                         if idx >= filter.args.len() {
-                            return Err(anyhow!(" ? exceeds the number of args"));
+                            return Err(Error::ArgCountMismatch);
                         }
-                        args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                        let field = self.model.redacted_filter(&query);
+                        let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                            .map_err(|err| arg_error(&query, err))?;
+                        args.push_str(&shown(arg, &*filter.args[idx], field));
                         args.push(' ');
                         query.push_str("@P");
                         query.push_str(&params.len().to_string());
@@ -814,18 +1343,27 @@ where
         debug_assert!(!quo, "join on statement quotation mark not closed error");
 
         //where statement section
+        let scope = self.model.scope(QUOTE);
+        let ids = self.model.id_columns()?;
+        let more = scope.is_some() || !ids.is_empty();
         if filter.expr.len() > 0 {
             query.push_str(" where ");
+            if more {
+                query.push('(');
+            }
 
-            for ch in filter.expr.chars() {
+            for ch in quote_expr(&filter.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= filter.args.len() {
-                        return Err(anyhow!(" ? exceeds the number of args"));
+                        return Err(Error::ArgCountMismatch);
                     }
-                    args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                    let field = self.model.redacted_filter(&query);
+                    let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                        .map_err(|err| arg_error(&query, err))?;
+                    args.push_str(&shown(arg, &*filter.args[idx], field));
                     args.push(' ');
                     query.push_str("@P");
                     query.push_str(&params.len().to_string());
@@ -834,7 +1372,19 @@ where
                     query.push(ch);
                 }
             }
+            if more {
+                query.push(')');
+            }
         }
+        mssql_push_conditions!(
+            self,
+            query,
+            params,
+            args,
+            scope,
+            ids,
+            !filter.expr.is_empty()
+        );
         debug_assert!(!quo, "where statement quotation mark not closed error");
 
         //group by statement section
@@ -853,9 +1403,12 @@ where
                         }
                         if ch == '?' && !quo {
                             if idx >= filter.args.len() {
-                                return Err(anyhow!(" ? exceeds the number of args"));
+                                return Err(Error::ArgCountMismatch);
                             }
-                            args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                            let field = self.model.redacted_filter(&query);
+                            let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                                .map_err(|err| arg_error(&query, err))?;
+                            args.push_str(&shown(arg, &*filter.args[idx], field));
                             args.push(' ');
                             query.push_str("@P");
                             query.push_str(&params.len().to_string());
@@ -870,9 +1423,7 @@ where
         debug_assert!(!quo, "having statement quotation mark not closed error");
 
         if idx != filter.args.len() {
-            return Err(anyhow!(
-                "the quantity of ? is not equal to the number of parameters"
-            ));
+            return Err(Error::ArgCountMismatch);
         }
 
         //order by statement section
@@ -882,93 +1433,56 @@ where
         }
 
         //query column section
-        let fds = fnames
-            .iter()
-            .enumerate()
-            .filter_map(|(ix, fd)| {
-                if let Some(&co) = self.model.fields.get(fd) {
-                    if co == "-" {
-                        return None;
-                    }
-                }
-                Some((ix, fd))
-            })
-            .collect::<Vec<_>>();
+        let fds = self.model.read_fields();
 
         //execute sql statements
         let mut res = Vec::new();
-        let mut stream = match self.executor.query(&query, &params).await {
+        let deadline = self.model.deadline();
+        let (trace, binds) =
+            self.model
+                .trace(Kind::Select, false, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let mut stream = match timed!(
+            deadline,
+            trace,
+            self.executor.query(&query, &params),
+            &query,
+            discard
+        ) {
             Ok(stream) => stream,
-            Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
         };
-        while let Some(rst) = stream.next().await {
+        while let Some(rst) = timed!(deadline, trace, stream.next(), &query, discard) {
             match rst {
                 Ok(item) => {
                     if let Some(row) = item.as_row() {
                         let mut entity = self.model.entity.clone();
-                        for (ix, fd) in &fds {
+                        for (ix, fd) in fds.iter() {
                             (self.from_row)(fd, row, &mut entity[*ix])?;
                         }
                         res.push(entity);
                     }
                 }
-                Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
+                Err(err) => return Err(trace.fail(sql_error(query, args, err))),
             }
         }
+        trace.rows(res.len() as u64);
 
         Ok(res)
     }
 
-    fn limit(mut self, limit: &'a i64, offset: &'a i64) -> impl LimitExecutor<'a, T> {
-        self.limit = limit;
-        self.offset = offset;
-        self
-    }
-}
-
-#[cfg_attr(feature = "async_trait", async_trait::async_trait)]
-impl<'a, T, E, P, R> LimitExecutor<'a, T> for MssqlModel<'a, T, E, P, R>
-where
-    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
-    &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: AsyncRead + AsyncWrite + Unpin + Send,
-    P: Fn(&'a dyn Any, &mut Vec<&'a dyn ToSql>) -> Result<String> + Send,
-    R: Fn(&str, &Row, &mut dyn Any) -> Result<()> + Send,
-{
-    async fn query(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<Vec<T>> {
-        let (_, fnames) = !self.model.entity;
-
-        let mut query = "select * from (select ".to_string();
-
-        //select statement section
-        for fd in fnames {
-            if let Some(&co) = self.model.fields.get(fd) {
-                if co.len() > 0 {
-                    if co != "-" {
-                        query.push_str(co);
-                        query.push_str(" as ");
-                        query.push_str(fd);
-                        query.push(',');
-                    }
-                } else {
-                    query.push_str(fd);
-                    query.push(',');
-                }
-            }
-        }
-
-        query.push_str("row_number() over (order by ");
-        //order by statement section
-        if self.order.len() > 0 {
-            query.push_str(self.order);
-        } else {
-            query.push_str("(select 1)");
-        }
-        query.push_str(") as _num");
+    /// query statement of a page of the filter
+    async fn query_limit(
+        &mut self,
+        filter: &'a Filter,
+        other: Option<Other<'a>>,
+    ) -> Result<Vec<T>> {
+        //select and order by statement section
+        let mut query = page_select(&self.model.select_list(QUOTE), self.order);
 
         //from statement section
         query.push_str(" from ");
-        query.push_str(&self.model.table);
+        query.push_str(&quote_table(&self.model.table, QUOTE));
 
         let mut params: Vec<&dyn ToSql> = Vec::new();
         let mut args = " ".to_string();
@@ -986,9 +1500,12 @@ where
                     }
                     if ch == '?' && !quo {
                         if idx >= filter.args.len() {
-                            return Err(anyhow!(" ? exceeds the number of args"));
+                            return Err(Error::ArgCountMismatch);
                         }
-                        args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                        let field = self.model.redacted_filter(&query);
+                        let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                            .map_err(|err| arg_error(&query, err))?;
+                        args.push_str(&shown(arg, &*filter.args[idx], field));
                         args.push(' ');
                         query.push_str("@P");
                         query.push_str(&params.len().to_string());
@@ -1002,18 +1519,27 @@ where
         debug_assert!(!quo, "join on statement quotation mark not closed error");
 
         //where statement section
+        let scope = self.model.scope(QUOTE);
+        let ids = self.model.id_columns()?;
+        let more = scope.is_some() || !ids.is_empty();
         if filter.expr.len() > 0 {
             query.push_str(" where ");
+            if more {
+                query.push('(');
+            }
 
-            for ch in filter.expr.chars() {
+            for ch in quote_expr(&filter.expr, QUOTE).chars() {
                 if ch == '\'' {
                     quo = !quo;
                 }
                 if ch == '?' && !quo {
                     if idx >= filter.args.len() {
-                        return Err(anyhow!(" ? exceeds the number of args"));
+                        return Err(Error::ArgCountMismatch);
                     }
-                    args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                    let field = self.model.redacted_filter(&query);
+                    let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                        .map_err(|err| arg_error(&query, err))?;
+                    args.push_str(&shown(arg, &*filter.args[idx], field));
                     args.push(' ');
                     query.push_str("@P");
                     query.push_str(&params.len().to_string());
@@ -1022,7 +1548,19 @@ where
                     query.push(ch);
                 }
             }
+            if more {
+                query.push(')');
+            }
         }
+        mssql_push_conditions!(
+            self,
+            query,
+            params,
+            args,
+            scope,
+            ids,
+            !filter.expr.is_empty()
+        );
         debug_assert!(!quo, "where statement quotation mark not closed error");
 
         //group by statement section
@@ -1041,9 +1579,12 @@ where
                         }
                         if ch == '?' && !quo {
                             if idx >= filter.args.len() {
-                                return Err(anyhow!(" ? exceeds the number of args"));
+                                return Err(Error::ArgCountMismatch);
                             }
-                            args.push_str(&(self.to_arg)(&*filter.args[idx], &mut params)?);
+                            let field = self.model.redacted_filter(&query);
+                            let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                                .map_err(|err| arg_error(&query, err))?;
+                            args.push_str(&shown(arg, &*filter.args[idx], field));
                             args.push(' ');
                             query.push_str("@P");
                             query.push_str(&params.len().to_string());
@@ -1058,9 +1599,7 @@ where
         debug_assert!(!quo, "having statement quotation mark not closed error");
 
         if idx != filter.args.len() {
-            return Err(anyhow!(
-                "the quantity of ? is not equal to the number of parameters"
-            ));
+            return Err(Error::ArgCountMismatch);
         }
 
         //limit statement section
@@ -1080,40 +1619,481 @@ where
         query.push(')');
 
         //query column section
-        let fds = fnames
-            .iter()
-            .enumerate()
-            .filter_map(|(ix, fd)| {
-                if let Some(&co) = self.model.fields.get(fd) {
-                    if co == "-" {
-                        return None;
-                    }
-                }
-                Some((ix, fd))
-            })
-            .collect::<Vec<_>>();
+        let fds = self.model.read_fields();
 
         //execute sql statements
         let mut res = Vec::new();
-        let mut stream = match self.executor.query(&query, &params).await {
+        let deadline = self.model.deadline();
+        let (trace, binds) =
+            self.model
+                .trace(Kind::Select, false, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let mut stream = match timed!(
+            deadline,
+            trace,
+            self.executor.query(&query, &params),
+            &query,
+            discard
+        ) {
             Ok(stream) => stream,
-            Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
         };
-        while let Some(rst) = stream.next().await {
+        while let Some(rst) = timed!(deadline, trace, stream.next(), &query, discard) {
             match rst {
                 Ok(item) => {
                     if let Some(row) = item.as_row() {
                         let mut entity = self.model.entity.clone();
-                        for (ix, fd) in &fds {
+                        for (ix, fd) in fds.iter() {
                             (self.from_row)(fd, row, &mut entity[*ix])?;
                         }
                         res.push(entity);
                     }
                 }
-                Err(err) => return Err(anyhow!("sql:`{}` args:[{}]  {}", query, args, err)),
+                Err(err) => return Err(trace.fail(sql_error(query, args, err))),
+            }
+        }
+        trace.rows(res.len() as u64);
+
+        Ok(res)
+    }
+}
+
+#[cfg_attr(feature = "async_trait", async_trait::async_trait)]
+impl<'a, T, E, P, R> Executor<'a, T> for MssqlModel<'a, T, E, P, R>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+    &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    E: AsyncRead + AsyncWrite + Unpin + Send,
+    P: Fn(&'a dyn Any, &mut Vec<&'a dyn ToSql>) -> Result<String> + Send,
+    R: Fn(&str, &Row, &mut dyn Any) -> Result<()> + Send,
+{
+    async fn insert_one(self, filter: Option<&'a Filter>) -> Result<u64> {
+        let mut query = "insert into ".to_string();
+        query.push_str(&quote_table(&self.model.table, QUOTE));
+
+        //fields section
+        query.push('(');
+        query.push_str(&self.model.insert_list(QUOTE));
+        query.push(')');
+
+        let mut params: Vec<&dyn ToSql> = Vec::new();
+        let mut args = " ".to_string();
+
+        //values section
+        let mut sep = false;
+        if filter.is_some() {
+            query.push_str(" select ");
+        } else {
+            query.push_str(" values (");
+        }
+        for &(ix, fd) in self.model.insert_fields().iter() {
+            if sep {
+                query.push(',');
+            }
+            mssql_push_value!(
+                self,
+                query,
+                params,
+                args,
+                &self.model.entity[ix],
+                self.model.redacted(ix),
+                self.model.stamp(fd, true)
+            );
+            sep = true;
+        }
+
+        //where statement section
+        if let Some(flt) = filter {
+            query.push_str(" where ");
+
+            let mut idx = 0;
+            let mut quo = false;
+            for ch in quote_expr(&flt.expr, QUOTE).chars() {
+                if ch == '\'' {
+                    quo = !quo;
+                }
+                if ch == '?' && !quo {
+                    if idx >= flt.args.len() {
+                        return Err(Error::ArgCountMismatch);
+                    }
+                    let field = self.model.redacted_filter(&query);
+                    let arg = (self.to_arg)(&*flt.args[idx], &mut params)
+                        .map_err(|err| arg_error(&query, err))?;
+                    args.push_str(&shown(arg, &*flt.args[idx], field));
+                    args.push(' ');
+                    query.push_str("@P");
+                    query.push_str(&params.len().to_string());
+                    idx += 1;
+                } else {
+                    query.push(ch);
+                }
+            }
+            debug_assert!(!quo, "where statement quotation mark not closed error");
+
+            if idx != flt.args.len() {
+                return Err(Error::ArgCountMismatch);
+            }
+        } else {
+            query.push(')');
+        }
+
+        //execute sql statements
+        let (trace, binds) =
+            self.model
+                .trace(Kind::Insert, false, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let res = match timed!(
+            self.model.deadline(),
+            trace,
+            self.executor.execute(&query, &params),
+            &query,
+            discard
+        ) {
+            Ok(res) => res.total(),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
+        };
+        trace.rows(res);
+
+        Ok(res)
+    }
+
+    async fn insert(self, data: &'a [T]) -> Result<u64> {
+        let mut query = "insert into ".to_string();
+        query.push_str(&quote_table(&self.model.table, QUOTE));
+
+        //fields section
+        query.push('(');
+        query.push_str(&self.model.insert_list(QUOTE));
+        query.push_str(")  values ");
+
+        let mut params: Vec<&dyn ToSql> = Vec::new();
+        let mut args = String::new();
+
+        //values section
+        let mut sep = false;
+        for row in data {
+            if sep {
+                query.push(',');
+                args.push(' ');
+            }
+            query.push('(');
+            args.push_str("[ ");
+            let mut sp = false;
+            for &(ix, fd) in self.model.insert_fields().iter() {
+                if sp {
+                    query.push(',');
+                }
+                mssql_push_value!(
+                    self,
+                    query,
+                    params,
+                    args,
+                    &row[ix],
+                    self.model.redacted(ix),
+                    self.model.stamp(fd, true)
+                );
+                sp = true;
             }
+            query.push(')');
+            args.push(']');
+            sep = true;
         }
 
+        //execute sql statements
+        let (trace, binds) =
+            self.model
+                .trace(Kind::Insert, false, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let res = match timed!(
+            self.model.deadline(),
+            trace,
+            self.executor.execute(&query, &params),
+            &query,
+            discard
+        ) {
+            Ok(res) => res.total(),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
+        };
+        trace.rows(res);
+
         Ok(res)
     }
+
+    async fn insert_select(self, source: Source<'a>, filter: &'a Filter) -> Result<u64> {
+        let mut query = "insert into ".to_string();
+        query.push_str(&quote_table(&self.model.table, QUOTE));
+
+        //fields section
+        query.push('(');
+        query.push_str(&self.model.insert_list(QUOTE));
+        query.push(')');
+
+        let mut params: Vec<&dyn ToSql> = Vec::new();
+        let mut args = " ".to_string();
+
+        //select section
+        let mut sep = false;
+        query.push_str(" select ");
+        for &(ix, fd) in self.model.insert_fields().iter() {
+            if sep {
+                query.push(',');
+            }
+            match self.model.stamp(fd, true) {
+                Stamp::Entity => query.push_str(&quote(source.column(fd)?, QUOTE)),
+                stamp => {
+                    mssql_push_value!(
+                        self,
+                        query,
+                        params,
+                        args,
+                        &self.model.entity[ix],
+                        self.model.redacted(ix),
+                        stamp
+                    )
+                }
+            }
+            sep = true;
+        }
+        query.push_str(" from ");
+        query.push_str(&quote_table(&source.table, QUOTE));
+
+        let mut idx = 0;
+
+        //where statement section
+        if !filter.expr.is_empty() {
+            query.push_str(" where ");
+            if source.scope.is_some() {
+                query.push('(');
+            }
+
+            let mut quo = false;
+            for ch in quote_expr(&filter.expr, QUOTE).chars() {
+                if ch == '\'' {
+                    quo = !quo;
+                }
+                if ch == '?' && !quo {
+                    if idx >= filter.args.len() {
+                        return Err(Error::ArgCountMismatch);
+                    }
+                    let field = self.model.redacted_filter(&query);
+                    let arg = (self.to_arg)(&*filter.args[idx], &mut params)
+                        .map_err(|err| arg_error(&query, err))?;
+                    args.push_str(&shown(arg, &*filter.args[idx], field));
+                    args.push(' ');
+                    query.push_str("@P");
+                    query.push_str(&params.len().to_string());
+                    idx += 1;
+                } else {
+                    query.push(ch);
+                }
+            }
+            debug_assert!(!quo, "where statement quotation mark not closed error");
+            if source.scope.is_some() {
+                query.push(')');
+            }
+        }
+        if let Some((column, predicate)) = &source.scope {
+            query.push_str(if filter.expr.is_empty() {
+                " where "
+            } else {
+                " and "
+            });
+            query.push_str(&quote_table(&source.table, QUOTE));
+            query.push('.');
+            query.push_str(&quote(column, QUOTE));
+            query.push_str(predicate);
+        }
+
+        if idx != filter.args.len() {
+            return Err(Error::ArgCountMismatch);
+        }
+
+        //execute sql statements
+        let (trace, binds) =
+            self.model
+                .trace(Kind::Insert, false, &mut query, &args, params.len())?;
+        let params = bound(params, &binds);
+        let res = match timed!(
+            self.model.deadline(),
+            trace,
+            self.executor.execute(&query, &params),
+            &query,
+            discard
+        ) {
+            Ok(res) => res.total(),
+            Err(err) => return Err(trace.fail(sql_error(query, args, err))),
+        };
+        trace.rows(res);
+
+        Ok(res)
+    }
+
+    async fn update(mut self, filter: &'a Filter) -> Result<u64> {
+        check_filter(filter, "update")?;
+        let retry = self.retry_of(true).await?;
+        retried!(retry, async {
+            let tran = self.expect_begin().await?;
+            let res = self.update_filter(filter).await;
+            self.expect_end(tran, res).await
+        })
+    }
+
+    async fn update_all(mut self) -> Result<u64> {
+        let retry = self.retry_of(true).await?;
+        retried!(retry, async {
+            let tran = self.expect_begin().await?;
+            let res = self.update_filter(&ALL).await;
+            self.expect_end(tran, res).await
+        })
+    }
+
+    async fn update_returning(mut self, filter: &'a Filter) -> Result<Vec<T>> {
+        check_filter(filter, "update")?;
+        let tran = self.expect_begin().await?;
+        let res = self.update_output(filter).await;
+        self.expect_end(tran, res).await
+    }
+
+    async fn update_many(mut self, data: &'a [T], keys: &'a [&'a str]) -> Result<Vec<u64>> {
+        let retry = self.retry_of(true).await?;
+        Ok(retried!(retry, async {
+            let tran = self.expect_begin().await?;
+            let res = self.merge_rows(data, keys).await;
+            self.expect_end(tran, res).await
+        })?
+        .0)
+    }
+
+    async fn delete(mut self, filter: &'a Filter) -> Result<u64> {
+        check_filter(filter, "delete")?;
+        let retry = self.retry_of(true).await?;
+        retried!(retry, async {
+            let tran = self.expect_begin().await?;
+            let res = self.delete_filter(filter, false).await;
+            self.expect_end(tran, res).await
+        })
+    }
+
+    async fn delete_returning(mut self, filter: &'a Filter) -> Result<Vec<T>> {
+        check_filter(filter, "delete")?;
+        let tran = self.expect_begin().await?;
+        let res = self.delete_output(filter).await;
+        self.expect_end(tran, res).await
+    }
+
+    async fn delete_all(mut self) -> Result<u64> {
+        let retry = self.retry_of(true).await?;
+        retried!(retry, async {
+            let tran = self.expect_begin().await?;
+            let res = self.delete_filter(&ALL, false).await;
+            self.expect_end(tran, res).await
+        })
+    }
+
+    async fn hard_delete(mut self, filter: &'a Filter) -> Result<u64> {
+        check_filter(filter, "delete")?;
+        let retry = self.retry_of(true).await?;
+        retried!(retry, async {
+            let tran = self.expect_begin().await?;
+            let res = self.delete_filter(filter, true).await;
+            self.expect_end(tran, res).await
+        })
+    }
+
+    async fn count(mut self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<i64> {
+        let retry = self.retry_of(false).await?;
+        retried!(retry, self.count_filter(filter, other))
+    }
+
+    async fn find_by_id(mut self) -> Result<T> {
+        self.model.by_id();
+        OrderExecutor::query_one(self, &ALL, None).await
+    }
+
+    async fn exists_by_id(mut self) -> Result<bool> {
+        self.model.by_id();
+        Ok(self.count(&ALL, None).await? > 0)
+    }
+
+    async fn update_by_id(mut self) -> Result<u64> {
+        self.model.by_id();
+        self.update_all().await
+    }
+
+    async fn delete_by_id(mut self) -> Result<u64> {
+        self.model.by_id();
+        self.delete_all().await
+    }
+
+    async fn save(mut self) -> Result<u64> {
+        if self.model.id_unset()? {
+            self.model.skip_keys();
+            self.insert_one(None).await
+        } else {
+            self.update_by_id().await
+        }
+    }
+
+    fn order_by(mut self, order: &'a str) -> impl OrderExecutor<'a, T> {
+        self.order = order;
+        self
+    }
+}
+
+#[cfg_attr(feature = "async_trait", async_trait::async_trait)]
+impl<'a, T, E, P, R> OrderExecutor<'a, T> for MssqlModel<'a, T, E, P, R>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+    &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    E: AsyncRead + AsyncWrite + Unpin + Send,
+    P: Fn(&'a dyn Any, &mut Vec<&'a dyn ToSql>) -> Result<String> + Send,
+    R: Fn(&str, &Row, &mut dyn Any) -> Result<()> + Send,
+{
+    async fn query_one(mut self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<T> {
+        let retry = self.retry_of(false).await?;
+        retried!(retry, self.query_one_filter(filter, other))
+    }
+
+    async fn query(mut self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<Vec<T>> {
+        let retry = self.retry_of(false).await?;
+        retried!(retry, self.query_filter(filter, other))
+    }
+
+    fn limit(mut self, limit: &'a i64, offset: &'a i64) -> impl LimitExecutor<'a, T> {
+        self.limit = limit;
+        self.offset = offset;
+        self
+    }
+}
+
+#[cfg_attr(feature = "async_trait", async_trait::async_trait)]
+impl<'a, T, E, P, R> LimitExecutor<'a, T> for MssqlModel<'a, T, E, P, R>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+    &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    E: AsyncRead + AsyncWrite + Unpin + Send,
+    P: Fn(&'a dyn Any, &mut Vec<&'a dyn ToSql>) -> Result<String> + Send,
+    R: Fn(&str, &Row, &mut dyn Any) -> Result<()> + Send,
+{
+    async fn query(mut self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<Vec<T>> {
+        let retry = self.retry_of(false).await?;
+        retried!(retry, self.query_limit(filter, other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_selects() {
+        assert_eq!(
+            page_select("[pid],[user] as [user_id]", "pid desc"),
+            "select * from (select [pid],[user] as [user_id],row_number() over (order by pid desc) as _num"
+        );
+        assert_eq!(
+            page_select("", ""),
+            "select * from (select row_number() over (order by (select 1)) as _num"
+        );
+    }
 }
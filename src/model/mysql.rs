@@ -1,14 +1,32 @@
 use std::{
     any::Any,
+    borrow::Cow,
     ops::{IndexMut, Not},
+    time::Duration,
 };
 
 use futures_util::StreamExt;
-use sqlx::{mysql::MySqlRow, MySql, MySqlExecutor, QueryBuilder, Row};
+use sqlx::{
+    error::DatabaseError, mysql::MySqlDatabaseError, mysql::MySqlRow, Acquire, MySql,
+    MySqlConnection, MySqlExecutor, MySqlPool, QueryBuilder, Row,
+};
+
+use crate::{anyhow, Error, Executor, Filter, LimitExecutor, OrderExecutor, Other, Result, Source};
+
+use super::{
+    arg_error, arguments, check_filter, check_rows, millis, quote, quote_expr, quote_table, shown,
+    sql_error, statement, Acquired, Backend, Bound, Conv, Isolation, Kind, Model, Quote, Stamp,
+    TxModel, ALL,
+};
+
+/// current time of the database
+const NOW: &str = "now()";
 
-use crate::{anyhow, Executor, Filter, LimitExecutor, OrderExecutor, Other, Result};
+/// identifier quotes of the dialect
+const QUOTE: Quote = ('`', '`', false);
 
-use super::Model;
+/// bound parameters allowed in a statement
+const PARAMS: usize = 65535;
 
 pub trait Mysql<'a, T> {
     /// bind a database connection
@@ -25,9 +43,10 @@ pub trait Mysql<'a, T> {
     /// ```
     fn bind<E>(self, executor: E) -> impl Executor<'a, T>
     where
-        E: MySqlExecutor<'a>;
+        E: MySqlExecutor<'a> + Acquire<'a, Database = MySql>;
 
-    /// bind database connections and customize conversion functions
+    /// bind database connections and customize conversion functions, the functions
+    /// are `Sync` since retried statements share them between attempts
     /// # Example
     /// ```no_run
     /// model.bind_conv(
@@ -54,9 +73,9 @@ pub trait Mysql<'a, T> {
     /// ```
     fn bind_conv<E, P, R>(self, executor: E, to_arg: P, from_row: R) -> impl Executor<'a, T>
     where
-        E: MySqlExecutor<'a>,
-        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send,
-        R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send;
+        E: MySqlExecutor<'a> + Acquire<'a, Database = MySql>,
+        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send + Sync,
+        R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send + Sync;
 }
 
 impl<'a, T> Mysql<'a, T> for Model<'a, T>
@@ -66,11 +85,11 @@ where
 {
     fn bind<E>(self, executor: E) -> impl Executor<'a, T>
     where
-        E: MySqlExecutor<'a>,
+        E: MySqlExecutor<'a> + Acquire<'a, Database = MySql>,
     {
         MysqlModel {
             model: self,
-            executor,
+            executor: Acquired(executor),
             to_arg: |value, query| {
                 sqlx_to_arg!(value, query, String, &str, f64, f32, i64, i32, i16, i8, bool)
             },
@@ -85,13 +104,13 @@ where
 
     fn bind_conv<E, P, R>(self, executor: E, to_arg: P, from_row: R) -> impl Executor<'a, T>
     where
-        E: MySqlExecutor<'a>,
-        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send,
-        R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send,
+        E: MySqlExecutor<'a> + Acquire<'a, Database = MySql>,
+        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send + Sync,
+        R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send + Sync,
     {
         MysqlModel {
             model: self,
-            executor,
+            executor: Acquired(executor),
             to_arg,
             from_row,
             order: "",
@@ -101,11 +120,85 @@ where
     }
 }
 
+impl<T> Backend<T> for MySqlPool {
+    fn executor<'a>(&'a mut self, model: Model<'a, T>) -> impl Executor<'a, T>
+    where
+        T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+        &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    {
+        model.bind(&*self)
+    }
+}
+
+sqlx_transact!(MySqlPool, MySqlConnection, MySql);
+
+/// mysql sets the isolation level of the next transaction before it begins
+async fn begin_statement(
+    conn: &mut MySqlConnection,
+    isolation: Option<Isolation>,
+) -> Result<Option<Cow<'static, str>>> {
+    match isolation {
+        Some(Isolation::Snapshot) => Err(anyhow!("mysql has no snapshot isolation").into()),
+        Some(level) => {
+            let sql = format!("set transaction isolation level {}", level.sql());
+            sqlx::query(&sql).execute(conn).await?;
+            Ok(None)
+        }
+        None => Ok(None),
+    }
+}
+
+/// deadlock and lock wait timeout of InnoDB
+pub(crate) fn retryable(err: &(dyn DatabaseError + 'static)) -> bool {
+    matches!(err.try_downcast_ref::<MySqlDatabaseError>(), Some(err) if matches!(err.number(), 1205 | 1213))
+}
+
+/// select stopped at its `MAX_EXECUTION_TIME`
+pub(crate) fn timed_out(err: &(dyn DatabaseError + 'static)) -> bool {
+    matches!(err.try_downcast_ref::<MySqlDatabaseError>(), Some(err) if err.number() == 3024)
+}
+
+/// select keyword with the server side limit of the timeout, the client side
+/// timeout that remains is returned with it
+fn select_head(timeout: Option<Duration>) -> (String, Option<Duration>) {
+    match timeout {
+        Some(timeout) => (
+            format!("select /*+ MAX_EXECUTION_TIME({}) */ ", millis(timeout)),
+            None,
+        ),
+        None => ("select ".to_string(), None),
+    }
+}
+
+/// owned pool with conversion functions coerced from closures
+pub type MysqlConv = Conv<
+    MySqlPool,
+    for<'b> fn(&'b dyn Any, &mut QueryBuilder<'b, MySql>) -> Result<String>,
+    fn(&str, &MySqlRow, &mut dyn Any) -> Result<()>,
+>;
+
+impl<T, P, R> Backend<T> for Conv<MySqlPool, P, R>
+where
+    P: for<'b> Fn(&'b dyn Any, &mut QueryBuilder<'b, MySql>) -> Result<String>
+        + Send
+        + Sync
+        + 'static,
+    R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send + Sync + 'static,
+{
+    fn executor<'a>(&'a mut self, model: Model<'a, T>) -> impl Executor<'a, T>
+    where
+        T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+        &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    {
+        model.bind_conv(&self.backend, &self.to_arg, &self.from_row)
+    }
+}
+
 struct MysqlModel<'a, T, E, P, R>
 where
-    E: MySqlExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send,
-    R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, MySql>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     model: Model<'a, T>,
     executor: E,
@@ -116,14 +209,184 @@ where
     offset: &'a i64,
 }
 
+impl<'a, T, E, P, R> MysqlModel<'a, T, E, P, R>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+    &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    E: Bound<'a, MySql>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send + Sync,
+{
+    /// mysql has no returning clause, the data is locked by select for update and
+    /// then updated or deleted in the same transaction
+    async fn modify_returning(self, filter: &'a Filter, update: bool) -> Result<Vec<T>> {
+        let table = self.model.table.clone();
+        let expect = self.model.expected();
+        let mut tx = Bound::transaction(self.executor).await?;
+        let my = TxModel {
+            model: self.model,
+            executor: &mut *tx,
+            to_arg: self.to_arg,
+            from_row: self.from_row,
+            order: self.order,
+            limit: self.limit,
+            offset: self.offset,
+        };
+
+        let (_, fnames) = !my.model.entity;
+        let version = if update {
+            my.model.version_column()?
+        } else {
+            None
+        };
+        let ids = my.model.id_columns()?;
+        let soft = if update { None } else { my.model.soft_delete };
+        let kind = if update { Kind::Update } else { Kind::Delete };
+        let scope = soft.map(|sd| sd.condition(&my.model.table, false, QUOTE));
+        let conds = ids.iter().chain(version.iter()).collect::<Vec<_>>();
+        let more = scope.is_some() || !conds.is_empty();
+
+        let mut builder = QueryBuilder::new("select ");
+        let mut args = " ".to_string();
+
+        //select statement section, updated fields are read as they will be written
+        let mut fds = Vec::new();
+        let mut sep = false;
+        for (ix, fd) in fnames.iter().enumerate() {
+            if !my.model.returned(fd) {
+                continue;
+            }
+            let co = my.model.column(fd);
+            let stamp = my.model.stamp(fd, false);
+            let written = update
+                && my.model.updatable(fd)
+                && !matches!(stamp, Stamp::Skip)
+                && !ids.iter().any(|(kx, _)| *kx == ix);
+            if written && matches!(stamp, Stamp::Entity) && version.map(|(vx, _)| vx) != Some(ix) {
+                continue;
+            }
+            if sep {
+                builder.push(",");
+            }
+            if matches!(version, Some((vx, _)) if vx == ix) {
+                builder.push(quote(co, QUOTE));
+                builder.push("+1");
+            } else if written {
+                sqlx_push_value!(
+                    my,
+                    builder,
+                    args,
+                    &my.model.entity[ix],
+                    my.model.redacted(ix),
+                    stamp
+                );
+            } else {
+                builder.push(quote(co, QUOTE));
+            }
+            builder.push(" as ");
+            builder.push(quote(fd, QUOTE));
+            fds.push((ix, fd));
+            sep = true;
+        }
+        if !sep {
+            builder.push("1");
+        }
+
+        //from statement section
+        builder.push(" from ");
+        builder.push(quote_table(&my.model.table, QUOTE));
+
+        let mut idx = 0;
+
+        //where statement section
+        if !filter.expr.is_empty() {
+            builder.push(" where ");
+            if more {
+                builder.push("(");
+            }
+
+            let mut quo = false;
+            for ch in quote_expr(&filter.expr, QUOTE).chars() {
+                if ch == '\'' {
+                    quo = !quo;
+                }
+                if ch == '?' && !quo {
+                    if idx >= filter.args.len() {
+                        return Err(Error::ArgCountMismatch);
+                    }
+                    let field = my.model.redacted_filter(builder.sql());
+                    let arg = (my.to_arg)(&*filter.args[idx], &mut builder)
+                        .map_err(|err| arg_error(builder.sql(), err))?;
+                    args.push_str(&shown(arg, &*filter.args[idx], field));
+                    args.push(' ');
+                    idx += 1;
+                } else {
+                    builder.push(ch);
+                }
+            }
+            debug_assert!(!quo, "where statement quotation mark not closed error");
+            if more {
+                builder.push(")");
+            }
+        }
+        sqlx_push_conditions!(my, builder, args, scope, conds, !filter.expr.is_empty());
+        builder.push(" for update");
+
+        if idx != filter.args.len() {
+            return Err(Error::ArgCountMismatch);
+        }
+
+        //execute sql statements
+        let mut res = Vec::new();
+        let mut sql = builder.sql().to_string();
+        let arguments = arguments(builder.build())?;
+        let (trace, binds) = my.model.trace(
+            kind,
+            soft.is_some(),
+            &mut sql,
+            &args,
+            sqlx::Arguments::len(&arguments),
+        )?;
+        let deadline = my.model.deadline();
+        let mut stream = statement(&sql, arguments, binds)?.fetch(&mut *my.executor);
+        while let Some(rst) = timed!(deadline, trace, stream.next(), &sql) {
+            match rst {
+                Ok(row) => {
+                    let mut entity = my.model.entity.clone();
+                    for (ix, fd) in &fds {
+                        (my.from_row)(fd, &row, &mut entity[*ix])?;
+                    }
+                    res.push(entity);
+                }
+                Err(err) => return Err(trace.fail(sql_error(&sql, args, err))),
+            }
+        }
+        trace.rows(res.len() as u64);
+        drop(stream);
+
+        let rows = if update {
+            sqlx_update!(my, filter)
+        } else {
+            sqlx_delete!(my, filter, false)
+        };
+        if let Err(err) = check_rows(&table, expect, rows) {
+            tx.rollback().await?;
+            return Err(err);
+        }
+        tx.commit().await?;
+
+        Ok(res)
+    }
+}
+
 #[cfg_attr(feature = "async_trait", async_trait::async_trait)]
 impl<'a, T, E, P, R> Executor<'a, T> for MysqlModel<'a, T, E, P, R>
 where
     T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
     &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: MySqlExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send,
-    R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, MySql>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     async fn insert_one(self, filter: Option<&'a Filter>) -> Result<u64> {
         Ok(sqlx_insert_one!(self, filter))
@@ -133,16 +396,110 @@ where
         Ok(sqlx_insert!(self, data))
     }
 
+    async fn insert_select(self, source: Source<'a>, filter: &'a Filter) -> Result<u64> {
+        Ok(sqlx_insert_select!(self, source, filter))
+    }
+
     async fn update(self, filter: &'a Filter) -> Result<u64> {
-        Ok(sqlx_update!(self, filter))
+        check_filter(filter, "update")?;
+        Ok(sqlx_retry!(self, true, sqlx_expect, sqlx_update, filter))
+    }
+
+    async fn update_returning(self, filter: &'a Filter) -> Result<Vec<T>> {
+        check_filter(filter, "update")?;
+        self.modify_returning(filter, true).await
+    }
+
+    async fn update_all(self) -> Result<u64> {
+        let filter = &ALL;
+        Ok(sqlx_retry!(self, true, sqlx_expect, sqlx_update, filter))
+    }
+
+    async fn update_many(self, data: &'a [T], keys: &'a [&'a str]) -> Result<Vec<u64>> {
+        Ok(sqlx_retry!(
+            self,
+            true,
+            sqlx_expect,
+            sqlx_update_many,
+            data,
+            keys,
+            sqlx_update_many_join
+        )
+        .0)
     }
 
     async fn delete(self, filter: &'a Filter) -> Result<u64> {
-        Ok(sqlx_delete!(self, filter))
+        check_filter(filter, "delete")?;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            sqlx_expect,
+            sqlx_delete,
+            filter,
+            false
+        ))
+    }
+
+    async fn delete_returning(self, filter: &'a Filter) -> Result<Vec<T>> {
+        check_filter(filter, "delete")?;
+        self.modify_returning(filter, false).await
+    }
+
+    async fn delete_all(self) -> Result<u64> {
+        let filter = &ALL;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            sqlx_expect,
+            sqlx_delete,
+            filter,
+            false
+        ))
+    }
+
+    async fn hard_delete(self, filter: &'a Filter) -> Result<u64> {
+        check_filter(filter, "delete")?;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            sqlx_expect,
+            sqlx_delete,
+            filter,
+            true
+        ))
     }
 
     async fn count(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<i64> {
-        Ok(sqlx_count!(self, filter, other))
+        Ok(sqlx_retry!(self, false, sqlx_count, filter, other))
+    }
+
+    async fn find_by_id(mut self) -> Result<T> {
+        self.model.by_id();
+        OrderExecutor::query_one(self, &ALL, None).await
+    }
+
+    async fn exists_by_id(mut self) -> Result<bool> {
+        self.model.by_id();
+        Ok(self.count(&ALL, None).await? > 0)
+    }
+
+    async fn update_by_id(mut self) -> Result<u64> {
+        self.model.by_id();
+        self.update_all().await
+    }
+
+    async fn delete_by_id(mut self) -> Result<u64> {
+        self.model.by_id();
+        self.delete_all().await
+    }
+
+    async fn save(mut self) -> Result<u64> {
+        if self.model.id_unset()? {
+            self.model.skip_keys();
+            self.insert_one(None).await
+        } else {
+            self.update_by_id().await
+        }
     }
 
     fn order_by(mut self, order: &'a str) -> impl OrderExecutor<'a, T> {
@@ -156,19 +513,19 @@ impl<'a, T, E, P, R> OrderExecutor<'a, T> for MysqlModel<'a, T, E, P, R>
 where
     T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
     &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: MySqlExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send,
-    R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, MySql>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     async fn query_one(mut self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<T> {
         self.limit = &1;
         self.offset = &0;
-        let mut res = sqlx_query!(self, filter, other, true);
-        res.pop().ok_or(anyhow!("no data found"))
+        let mut res = sqlx_retry!(self, false, sqlx_query, filter, other, true);
+        res.pop().ok_or_else(|| Error::NotFound)
     }
 
     async fn query(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<Vec<T>> {
-        Ok(sqlx_query!(self, filter, other, false))
+        Ok(sqlx_retry!(self, false, sqlx_query, filter, other, false))
     }
 
     fn limit(mut self, limit: &'a i64, offset: &'a i64) -> impl LimitExecutor<'a, T> {
@@ -183,11 +540,11 @@ impl<'a, T, E, P, R> LimitExecutor<'a, T> for MysqlModel<'a, T, E, P, R>
 where
     T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
     &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: MySqlExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send,
-    R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, MySql>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, MySql>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &MySqlRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     async fn query(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<Vec<T>> {
-        Ok(sqlx_query!(self, filter, other, true))
+        Ok(sqlx_retry!(self, false, sqlx_query, filter, other, true))
     }
 }
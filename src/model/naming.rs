@@ -0,0 +1,210 @@
+use std::sync::RwLock;
+
+use super::meta::clear_metas;
+
+/// how the struct name is turned into a table name
+#[derive(Clone, Copy)]
+pub enum NamingStrategy {
+    /// camel hump to underline, `OpLog` -> `op_log`
+    Snake,
+    /// underline and plural, `OpLog` -> `op_logs`
+    Plural,
+    /// custom conversion of the struct name
+    Custom(fn(&str) -> String),
+}
+
+/// table naming of the entities
+/// # Example
+/// ```no_run
+/// // OpLog -> audit.t_op_logs
+/// set_naming(Naming {
+///     strategy: NamingStrategy::Plural,
+///     prefix: "t_",
+///     schema: "audit",
+/// });
+/// ```
+#[derive(Clone, Copy)]
+pub struct Naming {
+    /// conversion of the struct name
+    pub strategy: NamingStrategy,
+    /// prepended to the converted name
+    pub prefix: &'static str,
+    /// schema qualifying the table name, empty for none
+    pub schema: &'static str,
+}
+
+impl Default for Naming {
+    fn default() -> Self {
+        Self {
+            strategy: NamingStrategy::Snake,
+            prefix: "",
+            schema: "",
+        }
+    }
+}
+
+impl Naming {
+    /// table name of a struct name
+    pub fn table(&self, name: &str) -> String {
+        let base = match self.strategy {
+            NamingStrategy::Snake => snake(name),
+            NamingStrategy::Plural => plural(snake(name)),
+            NamingStrategy::Custom(conv) => conv(name),
+        };
+        let mut table = String::new();
+        if !self.schema.is_empty() {
+            table.push_str(self.schema);
+            table.push('.');
+        }
+        table.push_str(self.prefix);
+        table.push_str(&base);
+        table
+    }
+}
+
+static NAMING: RwLock<Option<Naming>> = RwLock::new(None);
+static ENTITY_NAMINGS: RwLock<Vec<(&'static str, Naming)>> = RwLock::new(Vec::new());
+
+/// table naming of all entities without their own naming
+pub fn set_naming(naming: Naming) {
+    *NAMING.write().unwrap_or_else(|e| e.into_inner()) = Some(naming);
+    clear_metas();
+}
+
+/// table naming of one entity by its struct name
+/// # Example
+/// ```no_run
+/// set_entity_naming(
+///     "OpLog",
+///     Naming {
+///         schema: "dbo",
+///         ..Default::default()
+///     },
+/// );
+/// ```
+pub fn set_entity_naming(entity: &'static str, naming: Naming) {
+    let mut namings = ENTITY_NAMINGS.write().unwrap_or_else(|e| e.into_inner());
+    match namings.iter_mut().find(|(name, _)| *name == entity) {
+        Some((_, nm)) => *nm = naming,
+        None => namings.push((entity, naming)),
+    }
+    drop(namings);
+    clear_metas();
+}
+
+/// naming used by a struct name, the entity one first and then the global one
+pub(crate) fn naming_of(entity: &str) -> Naming {
+    let namings = ENTITY_NAMINGS.read().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, naming)) = namings.iter().find(|(name, _)| *name == entity) {
+        return *naming;
+    }
+    NAMING
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .unwrap_or_default()
+}
+
+/// camel hump to underline
+fn snake(name: &str) -> String {
+    let mut table = String::new();
+    for ch in name.chars() {
+        if ch.is_uppercase() {
+            if !table.is_empty() {
+                table.push('_');
+            }
+            table.push_str(&ch.to_lowercase().to_string());
+        } else {
+            table.push(ch);
+        }
+    }
+    table
+}
+
+/// english plural of a word
+fn plural(mut word: String) -> String {
+    let consonant_y = word.ends_with('y')
+        && !matches!(
+            word.chars().rev().nth(1),
+            Some('a' | 'e' | 'i' | 'o' | 'u') | None
+        );
+    if consonant_y {
+        word.pop();
+        word.push_str("ies");
+    } else if word.ends_with('s')
+        || word.ends_with('x')
+        || word.ends_with('z')
+        || word.ends_with("ch")
+        || word.ends_with("sh")
+    {
+        word.push_str("es");
+    } else {
+        word.push('s');
+    }
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snakes() {
+        assert_eq!(snake("OpLog"), "op_log");
+        assert_eq!(snake("Oplog"), "oplog");
+        assert_eq!(snake("opLog2"), "op_log2");
+    }
+
+    #[test]
+    fn plurals() {
+        assert_eq!(plural("op_log".to_string()), "op_logs");
+        assert_eq!(plural("category".to_string()), "categories");
+        assert_eq!(plural("day".to_string()), "days");
+        assert_eq!(plural("class".to_string()), "classes");
+        assert_eq!(plural("box".to_string()), "boxes");
+        assert_eq!(plural("batch".to_string()), "batches");
+        assert_eq!(plural("y".to_string()), "ys");
+    }
+
+    #[test]
+    fn tables() {
+        let naming = Naming {
+            strategy: NamingStrategy::Plural,
+            prefix: "t_",
+            schema: "audit",
+        };
+        assert_eq!(naming.table("OpLog"), "audit.t_op_logs");
+        assert_eq!(Naming::default().table("OpLog"), "op_log");
+        let naming = Naming {
+            strategy: NamingStrategy::Custom(|name| name.to_uppercase()),
+            ..Default::default()
+        };
+        assert_eq!(naming.table("OpLog"), "OPLOG");
+    }
+
+    #[test]
+    fn cached_metas() {
+        use super::super::meta::meta_of;
+        const FIELDS: &[&str] = &["id"];
+        let first = meta_of("NamingProbe", FIELDS);
+        assert_eq!(first.table, "naming_probe");
+        set_entity_naming(
+            "NamingProbe",
+            Naming {
+                prefix: "t_",
+                ..Default::default()
+            },
+        );
+        let prefixed = meta_of("NamingProbe", FIELDS);
+        assert_eq!(prefixed.table, "t_naming_probe");
+        set_entity_naming("NamingProbe", Naming::default());
+        assert!(std::ptr::eq(meta_of("NamingProbe", FIELDS), first));
+        set_entity_naming(
+            "NamingProbe",
+            Naming {
+                prefix: "t_",
+                ..Default::default()
+            },
+        );
+        assert!(std::ptr::eq(meta_of("NamingProbe", FIELDS), prefixed));
+    }
+}
@@ -0,0 +1,402 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    ops::{IndexMut, Not},
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::{Executor, Filter, LimitExecutor, OrderExecutor, Other, Result, RowCount, Source};
+
+use super::{
+    column_of, meta_of, Entity, Middleware, Model, Retry, Role, SoftDelete, Timestamps, Trashed,
+};
+
+/// owned database handle, a model is bound to a borrow of it when the query runs
+pub trait Backend<T>: Send + 'static {
+    /// bind a model to the handle with the default conversion functions
+    fn executor<'a>(&'a mut self, model: Model<'a, T>) -> impl Executor<'a, T>
+    where
+        T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+        &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>;
+}
+
+/// model owning its entity, it can be kept in a struct or moved into a task
+/// # Example
+/// ```no_run
+/// let mut model = OwnedModel::new(Oplog::default());
+/// model.keys = vec!["pid"];
+/// let query = model.bind(pool.clone()).order_by("pid").limit(10, 0);
+/// let res = tokio::spawn(query.query(Filter::default(), None)).await??;
+/// ```
+pub struct OwnedModel<T> {
+    entity: T,
+    /// database table name
+    pub table: String,
+    /// column overrides of the fields, see [`Model::fields`]
+    pub fields: HashMap<&'static str, &'static str>,
+    /// roles of the fields, see [`Role`]
+    pub roles: HashMap<&'static str, Role>,
+    /// primary key fields
+    pub keys: Vec<&'static str>,
+    /// delete only marks the data and queries skip the marked data
+    pub soft_delete: Option<SoftDelete<'static>>,
+    /// version field of optimistic locking
+    pub version: Option<&'static str>,
+    /// created and updated time fields
+    pub timestamps: Option<Timestamps<'static>>,
+    /// fields whose values are masked in errors, see [`Model::redact`]
+    pub redact: Vec<&'static str>,
+    /// middleware of every statement, see [`Middleware`]
+    pub middleware: Vec<Arc<dyn Middleware>>,
+    /// retry policy of deadlocks and serialization failures, only idempotent
+    /// statements are run again, see [`Retry`]
+    pub retry: Option<Retry>,
+    /// timeout of each statement, see [`Model::timeout`]
+    pub timeout: Option<Duration>,
+    trashed: Trashed,
+    expect: Option<RowCount>,
+}
+
+impl<T> OwnedModel<T>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync + 'static,
+    for<'a> &'a T: Not<Output = (&'static str, &'static [&'static str])>,
+{
+    /// owned model of an entity with the naming of its table and fields, the
+    /// mappings of `#[derive(Entity)]` are only applied by [`OwnedModel::of`]
+    pub fn new(entity: T) -> OwnedModel<T> {
+        let (name, fnames) = !&entity;
+        let table = meta_of(name, fnames).table.clone();
+
+        OwnedModel {
+            entity,
+            table,
+            fields: HashMap::new(),
+            roles: HashMap::new(),
+            keys: Vec::new(),
+            soft_delete: None,
+            version: None,
+            timestamps: None,
+            redact: Vec::new(),
+            middleware: Vec::new(),
+            retry: None,
+            timeout: None,
+            trashed: Trashed::Exclude,
+            expect: None,
+        }
+    }
+
+    /// owned model of a derived entity with its declared mappings
+    pub fn of(entity: T) -> OwnedModel<T>
+    where
+        T: Entity,
+    {
+        let mut model = OwnedModel::new(entity);
+        if let Some(table) = T::TABLE {
+            model.table = table.to_string();
+        }
+        model.fields.extend(T::COLUMNS.iter().copied());
+        model.roles.extend(T::ROLES.iter().copied());
+        model.keys.extend(T::KEYS);
+        model.version = T::VERSION;
+        model.redact.extend(T::REDACT);
+        model
+    }
+
+    /// the owned entity
+    pub fn entity(&self) -> &T {
+        &self.entity
+    }
+
+    /// the owned entity, changed before the query runs
+    pub fn entity_mut(&mut self) -> &mut T {
+        &mut self.entity
+    }
+
+    /// queries contain soft deleted data
+    pub fn with_deleted(mut self) -> Self {
+        self.trashed = Trashed::Include;
+        self
+    }
+
+    /// queries only contain soft deleted data
+    pub fn only_deleted(mut self) -> Self {
+        self.trashed = Trashed::Only;
+        self
+    }
+
+    /// update and delete must change exactly this number of rows, otherwise they are rolled back
+    pub fn expect_rows(mut self, rows: u64) -> Self {
+        self.expect = Some(RowCount::Exactly(rows));
+        self
+    }
+
+    /// update and delete must change no more than this number of rows, otherwise they are rolled back
+    pub fn expect_at_most(mut self, rows: u64) -> Self {
+        self.expect = Some(RowCount::AtMost(rows));
+        self
+    }
+
+    /// update and delete must change exactly one row, otherwise they are rolled back
+    pub fn expect_one(self) -> Self {
+        self.expect_rows(1)
+    }
+
+    /// borrowed model with the same settings
+    pub fn model(&self) -> Model<'_, T> {
+        self.model_of(&self.entity)
+    }
+
+    /// borrowed model of another entity with the same settings
+    pub fn model_of<'a>(&'a self, entity: &'a T) -> Model<'a, T> {
+        let mut model = Model::new(entity);
+        model.table.clone_from(&self.table);
+        model.fields = self.fields.clone();
+        model.roles = self.roles.clone();
+        model.keys = self.keys.clone();
+        model.soft_delete = self.soft_delete;
+        model.version = self.version;
+        model.timestamps = self.timestamps;
+        model.redact = self.redact.clone();
+        model.middleware = self.middleware.iter().map(|m| &**m).collect();
+        model.trashed = self.trashed;
+        model.expect = self.expect;
+        model.timeout = self.timeout;
+        model.retry = self.retry;
+        model
+    }
+
+    /// owned select source of `insert_select`, see [`Source`]
+    /// # Example
+    /// ```no_run
+    /// let source = OwnedModel::new(Oplog::default()).source();
+    /// let query = OwnedModel::new(OplogArchive::default()).bind(pool.clone());
+    /// let res = tokio::spawn(query.insert_select(source, filter)).await??;
+    /// ```
+    pub fn source(&self) -> Source<'static> {
+        let model = self.model();
+        let (_, fnames) = !&self.entity;
+        let columns = fnames
+            .iter()
+            .filter(|&&fd| model.readable(fd))
+            .map(|&fd| (fd, column_of(&self.fields, fd)))
+            .collect();
+        //the borrowed predicate is rebuilt on the static column of the soft delete
+        let scope = self
+            .soft_delete
+            .zip(model.scope_predicate())
+            .map(|(sd, (_, predicate))| (sd.column, predicate));
+        Source {
+            table: self.table.clone(),
+            columns,
+            scope,
+        }
+    }
+
+    /// bind an owned database handle, such as a pool
+    /// # Example
+    /// ```no_run
+    /// let query = OwnedModel::new(Oplog::default()).bind(pool.clone());
+    /// ```
+    pub fn bind<B>(self, backend: B) -> OwnedQuery<T, B>
+    where
+        B: Backend<T>,
+    {
+        OwnedQuery {
+            model: self,
+            backend,
+            order: String::new(),
+            limit: None,
+        }
+    }
+}
+
+/// owned database handle with customized conversion functions, the conversion
+/// types of each backend are named `MysqlConv`, `PostgresConv`, `SqliteConv` and `MssqlConv`
+/// # Example
+/// ```no_run
+/// let backend: MysqlConv = Conv {
+///     backend: pool.clone(),
+///     to_arg: |value, query| {
+///         sqlx_to_arg!(value, query, String, &str, f64, f32, i64, i32, i16, i8, bool)
+///     },
+///     from_row: |name, row, value| {
+///         sqlx_from_row!(name, row, value, String, f64, f32, i64, i32, i16, i8, bool)
+///     },
+/// };
+/// let query = OwnedModel::new(Oplog::default()).bind(backend);
+/// ```
+pub struct Conv<B, P, R> {
+    /// owned database handle
+    pub backend: B,
+    /// conversion of the bound arguments
+    pub to_arg: P,
+    /// conversion of the queried columns
+    pub from_row: R,
+}
+
+/// owned model bound to an owned database handle, all statements are `Send + 'static`
+pub struct OwnedQuery<T, B> {
+    model: OwnedModel<T>,
+    backend: B,
+    order: String,
+    limit: Option<(i64, i64)>,
+}
+
+impl<T, B> OwnedQuery<T, B>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync + 'static,
+    for<'a> &'a T: Not<Output = (&'static str, &'static [&'static str])>,
+    B: Backend<T>,
+{
+    /// order by statement section
+    /// !!please note that there is an injection risk when using upload fields
+    pub fn order_by(mut self, order: impl Into<String>) -> Self {
+        self.order = order.into();
+        self
+    }
+
+    /// limit statement section
+    pub fn limit(mut self, limit: i64, offset: i64) -> Self {
+        self.limit = Some((limit, offset));
+        self
+    }
+
+    /// the database handle back
+    pub fn into_backend(self) -> B {
+        self.backend
+    }
+
+    /// insert the owned entity, it is only inserted when the filter holds
+    pub async fn insert_one(mut self, filter: Option<Filter>) -> Result<u64> {
+        let model = self.model.model();
+        let executor = self.backend.executor(model);
+        boxed(executor.insert_one(filter.as_ref())).await
+    }
+
+    /// insert some data
+    pub async fn insert(mut self, data: Vec<T>) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).insert(&data)).await
+    }
+
+    /// insert the data selected from the source, see [`OwnedModel::source`]
+    pub async fn insert_select(mut self, source: Source<'static>, filter: Filter) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).insert_select(source, &filter)).await
+    }
+
+    /// update data by the owned entity
+    pub async fn update(mut self, filter: Filter) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).update(&filter)).await
+    }
+
+    /// update data and return the changed data
+    pub async fn update_returning(mut self, filter: Filter) -> Result<Vec<T>> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).update_returning(&filter)).await
+    }
+
+    /// update all data of the table
+    pub async fn update_all(mut self) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).update_all()).await
+    }
+
+    /// update some data by their keys
+    pub async fn update_many(mut self, data: Vec<T>, keys: Vec<&'static str>) -> Result<Vec<u64>> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).update_many(&data, &keys)).await
+    }
+
+    /// delete data
+    pub async fn delete(mut self, filter: Filter) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).delete(&filter)).await
+    }
+
+    /// delete data and return the deleted data
+    pub async fn delete_returning(mut self, filter: Filter) -> Result<Vec<T>> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).delete_returning(&filter)).await
+    }
+
+    /// delete all data of the table
+    pub async fn delete_all(mut self) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).delete_all()).await
+    }
+
+    /// delete data even when the model is soft deleted
+    pub async fn hard_delete(mut self, filter: Filter) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).hard_delete(&filter)).await
+    }
+
+    /// count the data
+    pub async fn count(mut self, filter: Filter, other: Option<Other<'static>>) -> Result<i64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).count(&filter, other)).await
+    }
+
+    /// query the data by the keys of the owned entity
+    pub async fn find_by_id(mut self) -> Result<T> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).find_by_id()).await
+    }
+
+    /// whether data with the keys of the owned entity exists
+    pub async fn exists_by_id(mut self) -> Result<bool> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).exists_by_id()).await
+    }
+
+    /// update the data by the keys of the owned entity
+    pub async fn update_by_id(mut self) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).update_by_id()).await
+    }
+
+    /// delete the data by the keys of the owned entity
+    pub async fn delete_by_id(mut self) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).delete_by_id()).await
+    }
+
+    /// insert the owned entity when its key values are unset, otherwise update it by id
+    pub async fn save(mut self) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).save()).await
+    }
+
+    /// query a piece of data in the database
+    pub async fn query_one(mut self, filter: Filter, other: Option<Other<'static>>) -> Result<T> {
+        let model = self.model.model();
+        let executor = self.backend.executor(model).order_by(&self.order);
+        boxed(executor.query_one(&filter, other)).await
+    }
+
+    /// query some data in the database, paginated when a limit is set
+    pub async fn query(mut self, filter: Filter, other: Option<Other<'static>>) -> Result<Vec<T>> {
+        let model = self.model.model();
+        let executor = self.backend.executor(model).order_by(&self.order);
+        match &self.limit {
+            Some((limit, offset)) => {
+                boxed(executor.limit(limit, offset).query(&filter, other)).await
+            }
+            None => boxed(executor.query(&filter, other)).await,
+        }
+    }
+}
+
+/// box the future of a borrowed statement, so the owned future is `Send` without
+/// proving it for every borrow
+pub(crate) fn boxed<'a, O>(
+    future: impl Future<Output = O> + Send + 'a,
+) -> Pin<Box<dyn Future<Output = O> + Send + 'a>> {
+    Box::pin(future)
+}
@@ -1,14 +1,28 @@
 use std::{
     any::Any,
+    borrow::Cow,
     ops::{IndexMut, Not},
+    time::Duration,
 };
 
 use futures_util::StreamExt;
-use sqlx::{postgres::PgRow, PgExecutor, QueryBuilder, Row};
+use sqlx::{
+    error::DatabaseError, postgres::PgDatabaseError, postgres::PgRow, Acquire, PgConnection,
+    PgExecutor, PgPool, QueryBuilder, Row,
+};
+
+use crate::{anyhow, Error, Executor, Filter, LimitExecutor, OrderExecutor, Other, Result, Source};
+
+use super::{check_filter, Acquired, Backend, Bound, Conv, Isolation, Model, Plain, Quote, ALL};
 
-use crate::{anyhow, Executor, Filter, LimitExecutor, OrderExecutor, Other, Result};
+/// current time of the database
+const NOW: &str = "now()";
 
-use super::Model;
+/// identifier quotes of the dialect
+const QUOTE: Quote = ('"', '"', true);
+
+/// bound parameters allowed in a statement
+const PARAMS: usize = 65535;
 
 pub trait Postgres<'a, T> {
     /// bind a database connection
@@ -27,7 +41,8 @@ pub trait Postgres<'a, T> {
     where
         E: PgExecutor<'a>;
 
-    /// bind database connections and customize conversion functions
+    /// bind database connections and customize conversion functions, the functions
+    /// are `Sync` since retried statements share them between attempts
     /// # Example
     /// ```no_run
     /// model.bind_conv(
@@ -57,8 +72,32 @@ pub trait Postgres<'a, T> {
     fn bind_conv<E, P, R>(self, executor: E, to_arg: P, from_row: R) -> impl Executor<'a, T>
     where
         E: PgExecutor<'a>,
-        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send,
-        R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send;
+        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send + Sync,
+        R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send + Sync;
+
+    /// bind a database connection that can be acquired, it is needed by
+    /// expected row counts, batches of update_many, and the server side timeouts
+    /// and the retries of postgres
+    /// # Example
+    /// ```no_run
+    /// model.bind_acquire(&pool)
+    /// ```
+    fn bind_acquire<E>(self, executor: E) -> impl Executor<'a, T>
+    where
+        E: PgExecutor<'a> + Acquire<'a, Database = sqlx::Postgres>;
+
+    /// bind a database connection that can be acquired and customize conversion
+    /// functions like `bind_conv`
+    fn bind_acquire_conv<E, P, R>(
+        self,
+        executor: E,
+        to_arg: P,
+        from_row: R,
+    ) -> impl Executor<'a, T>
+    where
+        E: PgExecutor<'a> + Acquire<'a, Database = sqlx::Postgres>,
+        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send + Sync,
+        R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send + Sync;
 }
 
 impl<'a, T> Postgres<'a, T> for Model<'a, T>
@@ -72,7 +111,7 @@ where
     {
         PostgresModel {
             model: self,
-            executor,
+            executor: Plain(executor),
             to_arg: |value, query| {
                 sqlx_to_arg!(value, query, String, &str, f64, f32, i64, i32, i16, i8, bool)
             },
@@ -88,12 +127,48 @@ where
     fn bind_conv<E, P, R>(self, executor: E, to_arg: P, from_row: R) -> impl Executor<'a, T>
     where
         E: PgExecutor<'a>,
-        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send,
-        R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send,
+        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send + Sync,
+        R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send + Sync,
+    {
+        PostgresModel {
+            model: self,
+            executor: Plain(executor),
+            to_arg,
+            from_row,
+            order: "",
+            limit: &0,
+            offset: &0,
+        }
+    }
+
+    fn bind_acquire<E>(self, executor: E) -> impl Executor<'a, T>
+    where
+        E: PgExecutor<'a> + Acquire<'a, Database = sqlx::Postgres>,
+    {
+        PostgresModel {
+            model: self,
+            executor: Acquired(executor),
+            to_arg: |value, query| {
+                sqlx_to_arg!(value, query, String, &str, f64, f32, i64, i32, i16, i8, bool)
+            },
+            from_row: |name, row, value| {
+                sqlx_from_row!(name, row, value, String, f64, f32, i64, i32, i16, i8, bool)
+            },
+            order: "",
+            limit: &0,
+            offset: &0,
+        }
+    }
+
+    fn bind_acquire_conv<E, P, R>(self, executor: E, to_arg: P, from_row: R) -> impl Executor<'a, T>
+    where
+        E: PgExecutor<'a> + Acquire<'a, Database = sqlx::Postgres>,
+        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send + Sync,
+        R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send + Sync,
     {
         PostgresModel {
             model: self,
-            executor,
+            executor: Acquired(executor),
             to_arg,
             from_row,
             order: "",
@@ -103,11 +178,76 @@ where
     }
 }
 
+impl<T> Backend<T> for PgPool {
+    fn executor<'a>(&'a mut self, model: Model<'a, T>) -> impl Executor<'a, T>
+    where
+        T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+        &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    {
+        model.bind_acquire(&*self)
+    }
+}
+
+sqlx_transact!(PgPool, PgConnection, sqlx::Postgres);
+
+/// begin statement with the isolation level of the transaction
+async fn begin_statement(
+    _conn: &mut PgConnection,
+    isolation: Option<Isolation>,
+) -> Result<Option<Cow<'static, str>>> {
+    match isolation {
+        Some(Isolation::Snapshot) => Err(anyhow!("postgres has no snapshot isolation").into()),
+        Some(level) => Ok(Some(
+            format!("begin isolation level {}", level.sql()).into(),
+        )),
+        None => Ok(None),
+    }
+}
+
+/// serialization failure and deadlock
+pub(crate) fn retryable(err: &(dyn DatabaseError + 'static)) -> bool {
+    matches!(err.try_downcast_ref::<PgDatabaseError>(), Some(err) if matches!(err.code(), "40001" | "40P01"))
+}
+
+/// statement canceled at its `statement_timeout`
+pub(crate) fn timed_out(err: &(dyn DatabaseError + 'static)) -> bool {
+    matches!(err.try_downcast_ref::<PgDatabaseError>(), Some(err) if err.code() == "57014")
+}
+
+/// select keyword, the timeout is set on the transaction of the statement
+fn select_head(timeout: Option<Duration>) -> (String, Option<Duration>) {
+    ("select ".to_string(), timeout)
+}
+
+/// owned pool with conversion functions coerced from closures
+pub type PostgresConv = Conv<
+    PgPool,
+    for<'b> fn(&'b dyn Any, &mut QueryBuilder<'b, sqlx::Postgres>) -> Result<String>,
+    fn(&str, &PgRow, &mut dyn Any) -> Result<()>,
+>;
+
+impl<T, P, R> Backend<T> for Conv<PgPool, P, R>
+where
+    P: for<'b> Fn(&'b dyn Any, &mut QueryBuilder<'b, sqlx::Postgres>) -> Result<String>
+        + Send
+        + Sync
+        + 'static,
+    R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send + Sync + 'static,
+{
+    fn executor<'a>(&'a mut self, model: Model<'a, T>) -> impl Executor<'a, T>
+    where
+        T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+        &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    {
+        model.bind_acquire_conv(&self.backend, &self.to_arg, &self.from_row)
+    }
+}
+
 struct PostgresModel<'a, T, E, P, R>
 where
-    E: PgExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send,
-    R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, sqlx::Postgres>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     model: Model<'a, T>,
     executor: E,
@@ -123,28 +263,155 @@ impl<'a, T, E, P, R> Executor<'a, T> for PostgresModel<'a, T, E, P, R>
 where
     T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
     &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: PgExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send,
-    R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, sqlx::Postgres>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     async fn insert_one(self, filter: Option<&'a Filter>) -> Result<u64> {
-        Ok(sqlx_insert_one!(self, filter))
+        Ok(pg_timeout!(self, sqlx_insert_one, filter))
     }
 
     async fn insert(self, data: &'a [T]) -> Result<u64> {
-        Ok(sqlx_insert!(self, data))
+        Ok(pg_timeout!(self, sqlx_insert, data))
+    }
+
+    async fn insert_select(self, source: Source<'a>, filter: &'a Filter) -> Result<u64> {
+        Ok(pg_timeout!(self, sqlx_insert_select, source, filter))
     }
 
     async fn update(self, filter: &'a Filter) -> Result<u64> {
-        Ok(sqlx_update!(self, filter))
+        check_filter(filter, "update")?;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            pg_timeout,
+            sqlx_expect,
+            sqlx_update,
+            filter
+        ))
+    }
+
+    async fn update_returning(self, filter: &'a Filter) -> Result<Vec<T>> {
+        check_filter(filter, "update")?;
+        Ok(pg_timeout!(
+            self,
+            sqlx_expect,
+            sqlx_update,
+            filter,
+            sqlx_returning
+        ))
+    }
+
+    async fn update_all(self) -> Result<u64> {
+        let filter = &ALL;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            pg_timeout,
+            sqlx_expect,
+            sqlx_update,
+            filter
+        ))
+    }
+
+    async fn update_many(self, data: &'a [T], keys: &'a [&'a str]) -> Result<Vec<u64>> {
+        Ok(sqlx_retry!(
+            self,
+            true,
+            pg_timeout,
+            sqlx_expect,
+            sqlx_update_many,
+            data,
+            keys,
+            sqlx_update_many_from
+        )
+        .0)
     }
 
     async fn delete(self, filter: &'a Filter) -> Result<u64> {
-        Ok(sqlx_delete!(self, filter))
+        check_filter(filter, "delete")?;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            pg_timeout,
+            sqlx_expect,
+            sqlx_delete,
+            filter,
+            false
+        ))
+    }
+
+    async fn delete_returning(self, filter: &'a Filter) -> Result<Vec<T>> {
+        check_filter(filter, "delete")?;
+        Ok(pg_timeout!(
+            self,
+            sqlx_expect,
+            sqlx_delete,
+            filter,
+            false,
+            sqlx_returning
+        ))
+    }
+
+    async fn delete_all(self) -> Result<u64> {
+        let filter = &ALL;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            pg_timeout,
+            sqlx_expect,
+            sqlx_delete,
+            filter,
+            false
+        ))
+    }
+
+    async fn hard_delete(self, filter: &'a Filter) -> Result<u64> {
+        check_filter(filter, "delete")?;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            pg_timeout,
+            sqlx_expect,
+            sqlx_delete,
+            filter,
+            true
+        ))
     }
 
     async fn count(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<i64> {
-        Ok(sqlx_count!(self, filter, other))
+        Ok(sqlx_retry!(
+            self, false, pg_timeout, sqlx_count, filter, other
+        ))
+    }
+
+    async fn find_by_id(mut self) -> Result<T> {
+        self.model.by_id();
+        OrderExecutor::query_one(self, &ALL, None).await
+    }
+
+    async fn exists_by_id(mut self) -> Result<bool> {
+        self.model.by_id();
+        Ok(self.count(&ALL, None).await? > 0)
+    }
+
+    async fn update_by_id(mut self) -> Result<u64> {
+        self.model.by_id();
+        self.update_all().await
+    }
+
+    async fn delete_by_id(mut self) -> Result<u64> {
+        self.model.by_id();
+        self.delete_all().await
+    }
+
+    async fn save(mut self) -> Result<u64> {
+        if self.model.id_unset()? {
+            self.model.skip_keys();
+            self.insert_one(None).await
+        } else {
+            self.update_by_id().await
+        }
     }
 
     fn order_by(mut self, order: &'a str) -> impl OrderExecutor<'a, T> {
@@ -158,19 +425,21 @@ impl<'a, T, E, P, R> OrderExecutor<'a, T> for PostgresModel<'a, T, E, P, R>
 where
     T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
     &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: PgExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send,
-    R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, sqlx::Postgres>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     async fn query_one(mut self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<T> {
         self.limit = &1;
         self.offset = &0;
-        let mut res = sqlx_query!(self, filter, other, true);
-        res.pop().ok_or(anyhow!("no data found"))
+        let mut res = sqlx_retry!(self, false, pg_timeout, sqlx_query, filter, other, true);
+        res.pop().ok_or_else(|| Error::NotFound)
     }
 
     async fn query(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<Vec<T>> {
-        Ok(sqlx_query!(self, filter, other, false))
+        Ok(sqlx_retry!(
+            self, false, pg_timeout, sqlx_query, filter, other, false
+        ))
     }
 
     fn limit(mut self, limit: &'a i64, offset: &'a i64) -> impl LimitExecutor<'a, T> {
@@ -185,11 +454,13 @@ impl<'a, T, E, P, R> LimitExecutor<'a, T> for PostgresModel<'a, T, E, P, R>
 where
     T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
     &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: PgExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send,
-    R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, sqlx::Postgres>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Postgres>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &PgRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     async fn query(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<Vec<T>> {
-        Ok(sqlx_query!(self, filter, other, true))
+        Ok(sqlx_retry!(
+            self, false, pg_timeout, sqlx_query, filter, other, true
+        ))
     }
 }
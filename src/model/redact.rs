@@ -0,0 +1,131 @@
+use std::{
+    any::{Any, TypeId},
+    sync::RwLock,
+};
+
+/// masked form of a bound argument, to_arg may return it for sensitive values
+/// # Example
+/// ```no_run
+/// to_arg: |value, query| {
+///     if let Some(p) = value.downcast_ref::<Password>() {
+///         query.push_bind(&p.0);
+///         return Ok(MASK.to_string());
+///     }
+///     sqlx_to_arg!(value, query, String, &str, f64, f32, i64, i32, i16, i8, bool)
+/// },
+/// ```
+pub const MASK: &str = "***";
+
+/// arguments shown in errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Redaction {
+    /// arguments are shown except redacted types and fields
+    Marked,
+    /// every argument is masked, only the sql is shown
+    All,
+    /// redacted types and fields are shown too, for local debugging
+    Off,
+}
+
+static REDACTION: RwLock<Redaction> = RwLock::new(Redaction::Marked);
+static TYPES: RwLock<Vec<TypeId>> = RwLock::new(Vec::new());
+
+/// redaction of the arguments of all statements
+/// # Example
+/// ```no_run
+/// set_redaction(Redaction::All);
+/// ```
+pub fn set_redaction(redaction: Redaction) {
+    *REDACTION.write().unwrap_or_else(|e| e.into_inner()) = redaction;
+}
+
+/// mask every argument of a type, including filter arguments
+/// # Example
+/// ```no_run
+/// redact_type::<Password>();
+/// ```
+pub fn redact_type<V: Any>() {
+    let mut types = TYPES.write().unwrap_or_else(|e| e.into_inner());
+    if !types.contains(&TypeId::of::<V>()) {
+        types.push(TypeId::of::<V>());
+    }
+}
+
+/// argument rendered by to_arg as shown in errors, masked when the field or
+/// the type is redacted
+pub(crate) fn shown(arg: String, value: &dyn Any, field: bool) -> String {
+    let redaction = *REDACTION.read().unwrap_or_else(|e| e.into_inner());
+    let masked = match redaction {
+        Redaction::All => true,
+        Redaction::Off => false,
+        Redaction::Marked => {
+            field || {
+                let types = TYPES.read().unwrap_or_else(|e| e.into_inner());
+                !types.is_empty() && types.contains(&value.type_id())
+            }
+        }
+    };
+    if masked {
+        MASK.to_string()
+    } else {
+        arg
+    }
+}
+
+/// field compared to the filter argument placed after the sql, as `token` of
+/// `t.token = ?` or `lower(token) like ?`, placeholders and operators in between
+/// are skipped
+pub(crate) fn filter_field(sql: &str) -> Option<&str> {
+    const KEYWORDS: [&str; 8] = ["like", "ilike", "in", "not", "is", "between", "and", "or"];
+    let mut rest = sql;
+    loop {
+        rest = rest.trim_end_matches(|c: char| c.is_whitespace() || "=<>!(),?+-*/%|".contains(c));
+        let start = rest
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| !(c.is_alphanumeric() || "_.$@\"`[]".contains(c)))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &rest[start..];
+        if word.is_empty() {
+            return None;
+        }
+        let placeholder = word.starts_with('$') || word.starts_with("@P");
+        if placeholder || KEYWORDS.iter().any(|k| word.eq_ignore_ascii_case(k)) {
+            rest = &rest[..start];
+            continue;
+        }
+        let name = word.rsplit('.').next().unwrap_or(word);
+        return Some(name.trim_matches(|c| "\"`[]".contains(c)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_fields() {
+        assert_eq!(filter_field("token = "), Some("token"));
+        assert_eq!(filter_field("where t.\"token\"="), Some("token"));
+        assert_eq!(filter_field("lower([user].[token]) like "), Some("token"));
+        assert_eq!(filter_field("id in ($1,"), Some("id"));
+        assert_eq!(filter_field("age between @P1 and "), Some("age"));
+        assert_eq!(filter_field("name is not "), Some("name"));
+        assert_eq!(filter_field("("), None);
+    }
+
+    #[test]
+    fn shown_args() {
+        struct Secret;
+        redact_type::<Secret>();
+        assert_eq!(shown("1".to_string(), &1, false), "1");
+        assert_eq!(shown("1".to_string(), &1, true), MASK);
+        assert_eq!(shown("s".to_string(), &Secret, false), MASK);
+        set_redaction(Redaction::Off);
+        assert_eq!(shown("1".to_string(), &1, true), "1");
+        assert_eq!(shown("s".to_string(), &Secret, false), "s");
+        set_redaction(Redaction::All);
+        assert_eq!(shown("1".to_string(), &1, false), MASK);
+        set_redaction(Redaction::Marked);
+    }
+}
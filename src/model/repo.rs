@@ -0,0 +1,214 @@
+use std::{
+    any::Any,
+    ops::{IndexMut, Not},
+};
+
+use crate::{Executor, Filter, LimitExecutor, OrderExecutor, Other, Result, Source};
+
+use super::{owned::boxed, Backend, OwnedModel};
+
+/// repository of an entity type, it keeps the database handle and the model
+/// settings and runs any number of statements
+/// # Example
+/// ```no_run
+/// let mut model = OwnedModel::new(Oplog::default());
+/// model.keys = vec!["pid"];
+/// let mut repo = Repo::new(model, pool.clone());
+///
+/// repo.insert_one(&oplog, None).await?;
+/// let res = repo
+///     .order_by("optime desc")
+///     .limit(10, 0)
+///     .query(&Filter::default(), None)
+///     .await?;
+/// ```
+pub struct Repo<T, B> {
+    model: OwnedModel<T>,
+    backend: B,
+}
+
+impl<T, B> Repo<T, B>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync + 'static,
+    for<'a> &'a T: Not<Output = (&'static str, &'static [&'static str])>,
+    B: Backend<T>,
+{
+    /// repository with the settings of the model, its entity is used by the
+    /// statements without an entity argument
+    pub fn new(model: OwnedModel<T>, backend: B) -> Self {
+        Repo { model, backend }
+    }
+
+    /// model settings of the repository
+    pub fn model(&mut self) -> &mut OwnedModel<T> {
+        &mut self.model
+    }
+
+    /// the database handle
+    pub fn backend(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// insert an entity, it is only inserted when the filter holds
+    pub async fn insert_one(&mut self, entity: &T, filter: Option<&Filter>) -> Result<u64> {
+        let model = self.model.model_of(entity);
+        boxed(self.backend.executor(model).insert_one(filter)).await
+    }
+
+    /// insert some data
+    pub async fn insert(&mut self, data: &[T]) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).insert(data)).await
+    }
+
+    /// insert the data selected from the source, see [`Source`]
+    pub async fn insert_select(&mut self, source: Source<'_>, filter: &Filter) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).insert_select(source, filter)).await
+    }
+
+    /// update data by an entity
+    pub async fn update(&mut self, entity: &T, filter: &Filter) -> Result<u64> {
+        let model = self.model.model_of(entity);
+        boxed(self.backend.executor(model).update(filter)).await
+    }
+
+    /// update data by an entity and return the changed data
+    pub async fn update_returning(&mut self, entity: &T, filter: &Filter) -> Result<Vec<T>> {
+        let model = self.model.model_of(entity);
+        boxed(self.backend.executor(model).update_returning(filter)).await
+    }
+
+    /// update all data of the table by an entity
+    pub async fn update_all(&mut self, entity: &T) -> Result<u64> {
+        let model = self.model.model_of(entity);
+        boxed(self.backend.executor(model).update_all()).await
+    }
+
+    /// update some data by their keys
+    pub async fn update_many(&mut self, data: &[T], keys: &[&str]) -> Result<Vec<u64>> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).update_many(data, keys)).await
+    }
+
+    /// delete data
+    pub async fn delete(&mut self, filter: &Filter) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).delete(filter)).await
+    }
+
+    /// delete data and return the deleted data
+    pub async fn delete_returning(&mut self, filter: &Filter) -> Result<Vec<T>> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).delete_returning(filter)).await
+    }
+
+    /// delete all data of the table
+    pub async fn delete_all(&mut self) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).delete_all()).await
+    }
+
+    /// delete data even when the model is soft deleted
+    pub async fn hard_delete(&mut self, filter: &Filter) -> Result<u64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).hard_delete(filter)).await
+    }
+
+    /// count the data
+    pub async fn count(&mut self, filter: &Filter, other: Option<Other<'_>>) -> Result<i64> {
+        let model = self.model.model();
+        boxed(self.backend.executor(model).count(filter, other)).await
+    }
+
+    /// query the data by the keys of an entity
+    pub async fn find_by_id(&mut self, entity: &T) -> Result<T> {
+        let model = self.model.model_of(entity);
+        boxed(self.backend.executor(model).find_by_id()).await
+    }
+
+    /// whether data with the keys of an entity exists
+    pub async fn exists_by_id(&mut self, entity: &T) -> Result<bool> {
+        let model = self.model.model_of(entity);
+        boxed(self.backend.executor(model).exists_by_id()).await
+    }
+
+    /// update the data by the keys of an entity
+    pub async fn update_by_id(&mut self, entity: &T) -> Result<u64> {
+        let model = self.model.model_of(entity);
+        boxed(self.backend.executor(model).update_by_id()).await
+    }
+
+    /// delete the data by the keys of an entity
+    pub async fn delete_by_id(&mut self, entity: &T) -> Result<u64> {
+        let model = self.model.model_of(entity);
+        boxed(self.backend.executor(model).delete_by_id()).await
+    }
+
+    /// insert an entity when its key values are unset, otherwise update it by id
+    pub async fn save(&mut self, entity: &T) -> Result<u64> {
+        let model = self.model.model_of(entity);
+        boxed(self.backend.executor(model).save()).await
+    }
+
+    /// query a piece of data in the database
+    pub async fn query_one(&mut self, filter: &Filter, other: Option<Other<'_>>) -> Result<T> {
+        self.order_by("").query_one(filter, other).await
+    }
+
+    /// query some data in the database
+    pub async fn query(&mut self, filter: &Filter, other: Option<Other<'_>>) -> Result<Vec<T>> {
+        self.order_by("").query(filter, other).await
+    }
+
+    /// order by statement section
+    /// !!please note that there is an injection risk when using upload fields
+    pub fn order_by<'r>(&'r mut self, order: &'r str) -> RepoQuery<'r, T, B> {
+        RepoQuery {
+            repo: self,
+            order,
+            limit: None,
+        }
+    }
+}
+
+/// ordered and paginated query of a repository
+pub struct RepoQuery<'r, T, B> {
+    repo: &'r mut Repo<T, B>,
+    order: &'r str,
+    limit: Option<(i64, i64)>,
+}
+
+impl<'r, T, B> RepoQuery<'r, T, B>
+where
+    T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync + 'static,
+    for<'a> &'a T: Not<Output = (&'static str, &'static [&'static str])>,
+    B: Backend<T>,
+{
+    /// limit statement section
+    pub fn limit(mut self, limit: i64, offset: i64) -> Self {
+        self.limit = Some((limit, offset));
+        self
+    }
+
+    /// query a piece of data in the database
+    pub async fn query_one(self, filter: &Filter, other: Option<Other<'_>>) -> Result<T> {
+        let repo = self.repo;
+        let model = repo.model.model();
+        let executor = repo.backend.executor(model).order_by(self.order);
+        boxed(executor.query_one(filter, other)).await
+    }
+
+    /// query some data in the database, paginated when a limit is set
+    pub async fn query(self, filter: &Filter, other: Option<Other<'_>>) -> Result<Vec<T>> {
+        let repo = self.repo;
+        let model = repo.model.model();
+        let executor = repo.backend.executor(model).order_by(self.order);
+        match &self.limit {
+            Some((limit, offset)) => {
+                boxed(executor.limit(limit, offset).query(filter, other)).await
+            }
+            None => boxed(executor.query(filter, other)).await,
+        }
+    }
+}
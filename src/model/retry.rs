@@ -0,0 +1,185 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use crate::{Error, Result};
+
+/// retry policy of deadlocks and serialization failures, the delay before each
+/// retry doubles up to `max_backoff` and a random part of it is dropped
+/// # Example
+/// ```no_run
+/// let policy = Retry::new(5);
+/// let res = crudx::retry(&policy, || Model::new(&oplog).bind(&pool).count(&filter, None)).await?;
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Retry {
+    /// attempts including the first one
+    pub max_attempts: u32,
+    /// delay before the first retry
+    pub backoff: Duration,
+    /// upper bound of the delay
+    pub max_backoff: Duration,
+    /// random part of the delay, from 0.0 to 1.0
+    pub jitter: f64,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Retry {
+            max_attempts: 3,
+            backoff: Duration::from_millis(20),
+            max_backoff: Duration::from_secs(1),
+            jitter: 0.5,
+        }
+    }
+}
+
+impl Retry {
+    /// default policy with a number of attempts
+    pub fn new(max_attempts: u32) -> Self {
+        Retry {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// delay before the retry that follows an attempt
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt - 1).unwrap_or(u32::MAX);
+        let delay = self.backoff.saturating_mul(factor).min(self.max_backoff);
+        let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        delay.mul_f64(1.0 - self.jitter.clamp(0.0, 1.0) * random)
+    }
+}
+
+/// attempts of a statement under a retry policy
+pub(crate) struct Attempts {
+    retry: Option<Retry>,
+    attempt: u32,
+}
+
+impl Attempts {
+    pub(crate) fn new(retry: Option<Retry>) -> Self {
+        Attempts { retry, attempt: 0 }
+    }
+
+    /// delay before running again, none when the result is final
+    pub(crate) fn next<V>(&mut self, res: &Result<V>) -> Option<Duration> {
+        let retry = match (&self.retry, res) {
+            (Some(retry), Err(err)) if is_retryable(err) => retry,
+            _ => return None,
+        };
+        self.attempt += 1;
+        if self.attempt >= retry.max_attempts {
+            return None;
+        }
+        Some(retry.delay(self.attempt))
+    }
+}
+
+/// wait for the backoff delay
+pub(crate) async fn sleep(delay: Duration) {
+    futures_timer::Delay::new(delay).await
+}
+
+/// run the closure again when it fails with a deadlock or serialization failure,
+/// the closure must be safe to repeat, writes that are not idempotent are retried
+/// as a whole transaction by `transaction_retry`
+/// # Example
+/// ```no_run
+/// let res = crudx::retry(&Retry::default(), || {
+///     Model::new(&oplog).bind(&pool).order_by("pid").query(&filter, None)
+/// })
+/// .await?;
+/// ```
+pub async fn retry<F, Fut, R>(policy: &Retry, mut f: F) -> Result<R>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<R>>,
+{
+    let mut attempts = Attempts::new(Some(*policy));
+    loop {
+        let res = f().await;
+        match attempts.next(&res) {
+            Some(delay) => sleep(delay).await,
+            None => return res,
+        }
+    }
+}
+
+/// whether the error is a deadlock or serialization failure of the backend that
+/// may succeed when run again
+pub fn is_retryable(err: &Error) -> bool {
+    let mut chain = Some(err as &(dyn std::error::Error + 'static));
+    std::iter::from_fn(|| {
+        let cause = chain?;
+        chain = cause.source();
+        Some(cause)
+    })
+    .any(|cause| {
+        #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+        if let Some(sqlx::Error::Database(db)) = cause.downcast_ref::<sqlx::Error>() {
+            #[cfg(feature = "mysql")]
+            if super::mysql::retryable(&**db) {
+                return true;
+            }
+            #[cfg(feature = "postgres")]
+            if super::postgres::retryable(&**db) {
+                return true;
+            }
+            #[cfg(feature = "sqlite")]
+            if super::sqlite::retryable(&**db) {
+                return true;
+            }
+        }
+        #[cfg(feature = "mssql")]
+        if let Some(err) = cause.downcast_ref::<tiberius::error::Error>() {
+            return super::mssql::retryable(err);
+        }
+        let _ = cause;
+        false
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays() {
+        let retry = Retry::default();
+        for _ in 0..100 {
+            let delay = retry.delay(1);
+            assert!(delay >= Duration::from_millis(10) && delay <= Duration::from_millis(20));
+            let delay = retry.delay(3);
+            assert!(delay >= Duration::from_millis(40) && delay <= Duration::from_millis(80));
+            let delay = retry.delay(40);
+            assert!(delay >= Duration::from_millis(500) && delay <= Duration::from_secs(1));
+        }
+
+        let retry = Retry {
+            jitter: 0.0,
+            ..Retry::new(5)
+        };
+        assert_eq!(retry.delay(2), Duration::from_millis(40));
+        assert_eq!(retry.delay(10), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn final_results() {
+        let mut attempts = Attempts::new(Some(Retry::default()));
+        assert_eq!(attempts.next(&Ok(())), None);
+        assert_eq!(
+            attempts.next::<()>(&Err(anyhow::anyhow!("syntax error").into())),
+            None
+        );
+        let mut attempts = Attempts::new(None);
+        assert_eq!(
+            attempts.next::<()>(&Err(anyhow::anyhow!("deadlock").into())),
+            None
+        );
+    }
+}
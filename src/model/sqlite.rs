@@ -1,14 +1,28 @@
 use std::{
     any::Any,
+    borrow::Cow,
     ops::{IndexMut, Not},
+    time::Duration,
 };
 
 use futures_util::StreamExt;
-use sqlx::{sqlite::SqliteRow, QueryBuilder, Row, SqliteExecutor};
+use sqlx::{
+    error::DatabaseError, sqlite::SqliteError, sqlite::SqliteRow, Acquire, QueryBuilder, Row,
+    SqliteConnection, SqliteExecutor, SqlitePool,
+};
+
+use crate::{anyhow, Error, Executor, Filter, LimitExecutor, OrderExecutor, Other, Result, Source};
+
+use super::{check_filter, Acquired, Backend, Bound, Conv, Isolation, Model, Plain, Quote, ALL};
 
-use crate::{anyhow, Executor, Filter, LimitExecutor, OrderExecutor, Other, Result};
+/// current time of the database
+const NOW: &str = "current_timestamp";
 
-use super::Model;
+/// identifier quotes of the dialect
+const QUOTE: Quote = ('"', '"', false);
+
+/// bound parameters allowed in a statement
+const PARAMS: usize = 32766;
 
 pub trait Sqlite<'a, T> {
     /// bind a database connection
@@ -25,7 +39,8 @@ pub trait Sqlite<'a, T> {
     where
         E: SqliteExecutor<'a>;
 
-    /// bind database connections and customize conversion functions
+    /// bind database connections and customize conversion functions, the functions
+    /// are `Sync` since retried statements share them between attempts
     /// # Example
     /// ```no_run
     /// model.bind_conv(
@@ -53,8 +68,31 @@ pub trait Sqlite<'a, T> {
     fn bind_conv<E, P, R>(self, executor: E, to_arg: P, from_row: R) -> impl Executor<'a, T>
     where
         E: SqliteExecutor<'a>,
-        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send,
-        R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send;
+        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send + Sync,
+        R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send + Sync;
+
+    /// bind a database connection that can be acquired, it is needed by
+    /// expected row counts, update_many and retries
+    /// # Example
+    /// ```no_run
+    /// model.bind_acquire(&pool)
+    /// ```
+    fn bind_acquire<E>(self, executor: E) -> impl Executor<'a, T>
+    where
+        E: SqliteExecutor<'a> + Acquire<'a, Database = sqlx::Sqlite>;
+
+    /// bind a database connection that can be acquired and customize conversion
+    /// functions like `bind_conv`
+    fn bind_acquire_conv<E, P, R>(
+        self,
+        executor: E,
+        to_arg: P,
+        from_row: R,
+    ) -> impl Executor<'a, T>
+    where
+        E: SqliteExecutor<'a> + Acquire<'a, Database = sqlx::Sqlite>,
+        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send + Sync,
+        R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send + Sync;
 }
 
 impl<'a, T> Sqlite<'a, T> for Model<'a, T>
@@ -68,7 +106,7 @@ where
     {
         SqliteModel {
             model: self,
-            executor,
+            executor: Plain(executor),
             to_arg: |value, query| {
                 sqlx_to_arg!(value, query, String, &str, f64, f32, i64, i32, i16, i8, bool)
             },
@@ -84,12 +122,12 @@ where
     fn bind_conv<E, P, R>(self, executor: E, to_arg: P, from_row: R) -> impl Executor<'a, T>
     where
         E: SqliteExecutor<'a>,
-        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send,
-        R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send,
+        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send + Sync,
+        R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send + Sync,
     {
         SqliteModel {
             model: self,
-            executor,
+            executor: Plain(executor),
             to_arg,
             from_row,
             order: "",
@@ -97,13 +135,114 @@ where
             offset: &0,
         }
     }
+
+    fn bind_acquire<E>(self, executor: E) -> impl Executor<'a, T>
+    where
+        E: SqliteExecutor<'a> + Acquire<'a, Database = sqlx::Sqlite>,
+    {
+        SqliteModel {
+            model: self,
+            executor: Acquired(executor),
+            to_arg: |value, query| {
+                sqlx_to_arg!(value, query, String, &str, f64, f32, i64, i32, i16, i8, bool)
+            },
+            from_row: |name, row, value| {
+                sqlx_from_row!(name, row, value, String, f64, f32, i64, i32, i16, i8, bool)
+            },
+            order: "",
+            limit: &0,
+            offset: &0,
+        }
+    }
+
+    fn bind_acquire_conv<E, P, R>(self, executor: E, to_arg: P, from_row: R) -> impl Executor<'a, T>
+    where
+        E: SqliteExecutor<'a> + Acquire<'a, Database = sqlx::Sqlite>,
+        P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send + Sync,
+        R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send + Sync,
+    {
+        SqliteModel {
+            model: self,
+            executor: Acquired(executor),
+            to_arg,
+            from_row,
+            order: "",
+            limit: &0,
+            offset: &0,
+        }
+    }
+}
+
+impl<T> Backend<T> for SqlitePool {
+    fn executor<'a>(&'a mut self, model: Model<'a, T>) -> impl Executor<'a, T>
+    where
+        T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+        &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    {
+        model.bind_acquire(&*self)
+    }
+}
+
+sqlx_transact!(SqlitePool, SqliteConnection, sqlx::Sqlite);
+
+/// sqlite transactions are serializable, a serializable one takes the write lock when it begins
+async fn begin_statement(
+    _conn: &mut SqliteConnection,
+    isolation: Option<Isolation>,
+) -> Result<Option<Cow<'static, str>>> {
+    match isolation {
+        Some(Isolation::Snapshot) => Err(anyhow!("sqlite has no snapshot isolation").into()),
+        Some(Isolation::Serializable) => Ok(Some("begin immediate".into())),
+        _ => Ok(None),
+    }
+}
+
+/// busy and locked database, including their extended codes
+pub(crate) fn retryable(err: &(dyn DatabaseError + 'static)) -> bool {
+    let code = match err
+        .try_downcast_ref::<SqliteError>()
+        .and_then(|err| err.code())
+    {
+        Some(code) => code.parse::<i32>().unwrap_or_default(),
+        None => return false,
+    };
+    matches!(code & 0xff, 5 | 6)
+}
+
+/// select keyword, sqlite has no server side limit
+fn select_head(timeout: Option<Duration>) -> (String, Option<Duration>) {
+    ("select ".to_string(), timeout)
+}
+
+/// owned pool with conversion functions coerced from closures
+pub type SqliteConv = Conv<
+    SqlitePool,
+    for<'b> fn(&'b dyn Any, &mut QueryBuilder<'b, sqlx::Sqlite>) -> Result<String>,
+    fn(&str, &SqliteRow, &mut dyn Any) -> Result<()>,
+>;
+
+impl<T, P, R> Backend<T> for Conv<SqlitePool, P, R>
+where
+    P: for<'b> Fn(&'b dyn Any, &mut QueryBuilder<'b, sqlx::Sqlite>) -> Result<String>
+        + Send
+        + Sync
+        + 'static,
+    R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send + Sync + 'static,
+{
+    fn executor<'a>(&'a mut self, model: Model<'a, T>) -> impl Executor<'a, T>
+    where
+        T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
+        &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
+    {
+        model.bind_acquire_conv(&self.backend, &self.to_arg, &self.from_row)
+    }
 }
 
 struct SqliteModel<'a, T, E, P, R>
 where
-    E: SqliteExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send,
-    R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, sqlx::Sqlite>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     model: Model<'a, T>,
     executor: E,
@@ -119,9 +258,9 @@ impl<'a, T, E, P, R> Executor<'a, T> for SqliteModel<'a, T, E, P, R>
 where
     T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
     &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: SqliteExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send,
-    R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, sqlx::Sqlite>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     async fn insert_one(self, filter: Option<&'a Filter>) -> Result<u64> {
         Ok(sqlx_insert_one!(self, filter))
@@ -131,16 +270,107 @@ where
         Ok(sqlx_insert!(self, data))
     }
 
+    async fn insert_select(self, source: Source<'a>, filter: &'a Filter) -> Result<u64> {
+        Ok(sqlx_insert_select!(self, source, filter))
+    }
+
     async fn update(self, filter: &'a Filter) -> Result<u64> {
-        Ok(sqlx_update!(self, filter))
+        check_filter(filter, "update")?;
+        Ok(sqlx_retry!(self, true, sqlx_expect, sqlx_update, filter))
+    }
+
+    async fn update_returning(self, filter: &'a Filter) -> Result<Vec<T>> {
+        check_filter(filter, "update")?;
+        Ok(sqlx_expect!(self, sqlx_update, filter, sqlx_returning))
+    }
+
+    async fn update_all(self) -> Result<u64> {
+        let filter = &ALL;
+        Ok(sqlx_retry!(self, true, sqlx_expect, sqlx_update, filter))
+    }
+
+    async fn update_many(self, data: &'a [T], keys: &'a [&'a str]) -> Result<Vec<u64>> {
+        Ok(sqlx_retry!(self, true, sqlx_expect, sqlx_update_many_each, data, keys).0)
     }
 
     async fn delete(self, filter: &'a Filter) -> Result<u64> {
-        Ok(sqlx_delete!(self, filter))
+        check_filter(filter, "delete")?;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            sqlx_expect,
+            sqlx_delete,
+            filter,
+            false
+        ))
+    }
+
+    async fn delete_returning(self, filter: &'a Filter) -> Result<Vec<T>> {
+        check_filter(filter, "delete")?;
+        Ok(sqlx_expect!(
+            self,
+            sqlx_delete,
+            filter,
+            false,
+            sqlx_returning
+        ))
+    }
+
+    async fn delete_all(self) -> Result<u64> {
+        let filter = &ALL;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            sqlx_expect,
+            sqlx_delete,
+            filter,
+            false
+        ))
+    }
+
+    async fn hard_delete(self, filter: &'a Filter) -> Result<u64> {
+        check_filter(filter, "delete")?;
+        Ok(sqlx_retry!(
+            self,
+            true,
+            sqlx_expect,
+            sqlx_delete,
+            filter,
+            true
+        ))
     }
 
     async fn count(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<i64> {
-        Ok(sqlx_count!(self, filter, other))
+        Ok(sqlx_retry!(self, false, sqlx_count, filter, other))
+    }
+
+    async fn find_by_id(mut self) -> Result<T> {
+        self.model.by_id();
+        OrderExecutor::query_one(self, &ALL, None).await
+    }
+
+    async fn exists_by_id(mut self) -> Result<bool> {
+        self.model.by_id();
+        Ok(self.count(&ALL, None).await? > 0)
+    }
+
+    async fn update_by_id(mut self) -> Result<u64> {
+        self.model.by_id();
+        self.update_all().await
+    }
+
+    async fn delete_by_id(mut self) -> Result<u64> {
+        self.model.by_id();
+        self.delete_all().await
+    }
+
+    async fn save(mut self) -> Result<u64> {
+        if self.model.id_unset()? {
+            self.model.skip_keys();
+            self.insert_one(None).await
+        } else {
+            self.update_by_id().await
+        }
     }
 
     fn order_by(mut self, order: &'a str) -> impl OrderExecutor<'a, T> {
@@ -154,19 +384,19 @@ impl<'a, T, E, P, R> OrderExecutor<'a, T> for SqliteModel<'a, T, E, P, R>
 where
     T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
     &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: SqliteExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send,
-    R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, sqlx::Sqlite>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     async fn query_one(mut self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<T> {
         self.limit = &1;
         self.offset = &0;
-        let mut res = sqlx_query!(self, filter, other, true);
-        res.pop().ok_or(anyhow!("no data found"))
+        let mut res = sqlx_retry!(self, false, sqlx_query, filter, other, true);
+        res.pop().ok_or_else(|| Error::NotFound)
     }
 
     async fn query(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<Vec<T>> {
-        Ok(sqlx_query!(self, filter, other, false))
+        Ok(sqlx_retry!(self, false, sqlx_query, filter, other, false))
     }
 
     fn limit(mut self, limit: &'a i64, offset: &'a i64) -> impl LimitExecutor<'a, T> {
@@ -181,11 +411,11 @@ impl<'a, T, E, P, R> LimitExecutor<'a, T> for SqliteModel<'a, T, E, P, R>
 where
     T: IndexMut<usize, Output = dyn Any> + Clone + Send + Sync,
     &'a T: 'a + Not<Output = (&'static str, &'static [&'static str])>,
-    E: SqliteExecutor<'a>,
-    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send,
-    R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send,
+    E: Bound<'a, sqlx::Sqlite>,
+    P: Fn(&'a dyn Any, &mut QueryBuilder<'a, sqlx::Sqlite>) -> Result<String> + Send + Sync,
+    R: Fn(&str, &SqliteRow, &mut dyn Any) -> Result<()> + Send + Sync,
 {
     async fn query(self, filter: &'a Filter, other: Option<Other<'a>>) -> Result<Vec<T>> {
-        Ok(sqlx_query!(self, filter, other, true))
+        Ok(sqlx_retry!(self, false, sqlx_query, filter, other, true))
     }
 }
@@ -0,0 +1,57 @@
+use std::{
+    error::Error,
+    future::Future,
+    pin::pin,
+    time::{Duration, Instant},
+};
+
+use futures_util::future::{select, Either};
+
+/// client side deadline of a statement, its round trips share it
+pub(crate) struct Deadline {
+    end: Option<Instant>,
+}
+
+impl Deadline {
+    pub(crate) fn new(timeout: Option<Duration>) -> Self {
+        Deadline {
+            end: timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+
+    /// run a round trip of the statement, it is dropped when the deadline passes
+    pub(crate) async fn run<F: Future>(&self, future: F) -> Option<F::Output> {
+        let end = match self.end {
+            Some(end) => end,
+            None => return Some(future.await),
+        };
+        let delay = futures_timer::Delay::new(end.saturating_duration_since(Instant::now()));
+        match select(pin!(future), delay).await {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(_) => None,
+        }
+    }
+}
+
+/// milliseconds of a server side limit, rounded up as 0 means no limit
+#[cfg_attr(not(any(feature = "postgres", feature = "mysql")), allow(dead_code))]
+pub(crate) fn millis(timeout: Duration) -> u128 {
+    timeout.as_nanos().div_ceil(1_000_000).max(1)
+}
+
+/// whether the database stopped the statement at its server side limit
+pub(crate) fn is_timeout(err: &(dyn Error + 'static)) -> bool {
+    #[cfg(any(feature = "postgres", feature = "mysql"))]
+    if let Some(sqlx::Error::Database(db)) = err.downcast_ref::<sqlx::Error>() {
+        #[cfg(feature = "mysql")]
+        if super::mysql::timed_out(&**db) {
+            return true;
+        }
+        #[cfg(feature = "postgres")]
+        if super::postgres::timed_out(&**db) {
+            return true;
+        }
+    }
+    let _ = err;
+    false
+}
@@ -0,0 +1,157 @@
+use std::{future::Future, mem};
+#[cfg(feature = "tracing")]
+use std::{
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use crate::Error;
+
+use super::{Arg, Kind, Middleware, Outcome, Statement};
+
+#[cfg(feature = "tracing")]
+static SLOW: RwLock<Option<Duration>> = RwLock::new(None);
+
+/// statements taking longer are warned about in their span, none turns it off
+/// # Example
+/// ```no_run
+/// set_slow_threshold(Some(Duration::from_millis(200)));
+/// ```
+#[cfg(feature = "tracing")]
+pub fn set_slow_threshold(threshold: Option<Duration>) {
+    *SLOW.write().unwrap_or_else(|e| e.into_inner()) = threshold;
+}
+
+/// span and middleware outcome of a statement, the span is closed with its
+/// elapsed time when dropped
+pub(crate) struct Trace<'m> {
+    kind: Kind,
+    soft: bool,
+    table: &'m str,
+    middleware: &'m [&'m dyn Middleware],
+    /// sql, args and binds kept for the middleware
+    sql: String,
+    args: String,
+    params: usize,
+    binds: Vec<Arg>,
+    done: bool,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    start: Instant,
+}
+
+impl<'m> Trace<'m> {
+    /// open the span of a statement of an operation and whether it is a soft
+    /// delete, the arguments are already redacted
+    pub(crate) fn start(
+        (kind, soft): (Kind, bool),
+        table: &'m str,
+        middleware: &'m [&'m dyn Middleware],
+        sql: &str,
+        args: &str,
+        params: usize,
+        binds: &[Arg],
+    ) -> Self {
+        let (kept_sql, kept_args, kept_binds) = if middleware.is_empty() {
+            (String::new(), String::new(), Vec::new())
+        } else {
+            (sql.to_string(), args.to_string(), binds.to_vec())
+        };
+        Trace {
+            kind,
+            soft,
+            table,
+            middleware,
+            sql: kept_sql,
+            args: kept_args,
+            params,
+            binds: kept_binds,
+            done: false,
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                target: "crudx",
+                "statement",
+                op = kind.as_str(),
+                soft,
+                table = %table,
+                sql = %sql,
+                args = %args.trim_end(),
+                rows = tracing::field::Empty,
+                elapsed_ms = tracing::field::Empty,
+                error = tracing::field::Empty,
+            ),
+            #[cfg(feature = "tracing")]
+            start: Instant::now(),
+        }
+    }
+
+    /// run a round trip of the statement inside its span
+    #[cfg(feature = "tracing")]
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> tracing::instrument::Instrumented<F> {
+        tracing::Instrument::instrument(future, self.span.clone())
+    }
+
+    /// run a round trip of the statement
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> F {
+        future
+    }
+
+    /// rows affected or returned by the statement
+    pub(crate) fn rows(mut self, rows: u64) {
+        #[cfg(feature = "tracing")]
+        self.span.record("rows", rows);
+        self.finish(Outcome::Rows(rows));
+    }
+
+    /// record the error of the statement and hand it back
+    pub(crate) fn fail(mut self, err: Error) -> Error {
+        #[cfg(feature = "tracing")]
+        self.span.record("error", tracing::field::display(&err));
+        self.finish(Outcome::Failed(&err));
+        err
+    }
+
+    /// hand the outcome to the middleware
+    fn finish(&mut self, outcome: Outcome<'_>) {
+        self.done = true;
+        if self.middleware.is_empty() {
+            return;
+        }
+        let statement = Statement {
+            kind: self.kind,
+            soft: self.soft,
+            table: self.table,
+            sql: mem::take(&mut self.sql),
+            args: &self.args,
+            params: self.params,
+            binds: mem::take(&mut self.binds),
+        };
+        for middleware in self.middleware {
+            middleware.after(&statement, outcome);
+        }
+    }
+}
+
+impl Drop for Trace<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.finish(Outcome::Dropped);
+        }
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed = self.start.elapsed();
+            self.span.record("elapsed_ms", elapsed.as_millis() as u64);
+            let slow = *SLOW.read().unwrap_or_else(|e| e.into_inner());
+            if slow.is_some_and(|slow| elapsed > slow) {
+                tracing::warn!(
+                    target: "crudx",
+                    parent: &self.span,
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    "slow statement"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::panic::{resume_unwind, AssertUnwindSafe};
+
+use futures_util::FutureExt;
+
+use crate::Result;
+
+use super::{sleep, Attempts, BoxFuture, Retry};
+
+/// isolation level of a transaction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Isolation {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+    /// row versioning of MsSQL
+    Snapshot,
+}
+
+impl Isolation {
+    /// sql name of the level
+    #[cfg_attr(
+        not(any(feature = "postgres", feature = "mysql", feature = "mssql")),
+        allow(dead_code)
+    )]
+    pub(crate) fn sql(&self) -> &'static str {
+        match self {
+            Isolation::ReadUncommitted => "read uncommitted",
+            Isolation::ReadCommitted => "read committed",
+            Isolation::RepeatableRead => "repeatable read",
+            Isolation::Serializable => "serializable",
+            Isolation::Snapshot => "snapshot",
+        }
+    }
+}
+
+/// database handle that opens transactions, a transaction opened inside another
+/// one is a savepoint
+pub trait Transact: Send {
+    /// open transaction, models are bound to `&mut **tx`
+    type Tx<'t>: Send
+    where
+        Self: 't;
+
+    /// open a transaction, or a savepoint when one is already open
+    fn begin(&mut self, isolation: Option<Isolation>) -> BoxFuture<'_, Result<Self::Tx<'_>>>;
+
+    /// commit the transaction or release the savepoint
+    fn commit(tx: Self::Tx<'_>) -> BoxFuture<'_, Result<()>>;
+
+    /// roll back the transaction or roll back to the savepoint
+    fn rollback(tx: Self::Tx<'_>) -> BoxFuture<'_, Result<()>>;
+}
+
+/// run the closure in a transaction, it is committed when the closure returns
+/// `Ok` and rolled back when it returns `Err` or panics
+/// # Example
+/// ```no_run
+/// let res = crudx::transaction(&mut pool, |tx| {
+///     Box::pin(async move {
+///         Model::new(&oplog).bind(&mut **tx).insert_one(None).await?;
+///         // savepoint
+///         crudx::transaction(&mut **tx, |sp| {
+///             Box::pin(async move { Model::new(&clazz).bind(&mut **sp).update_by_id().await })
+///         })
+///         .await
+///     })
+/// })
+/// .await?;
+/// ```
+pub async fn transaction<D, F, R>(db: &mut D, f: F) -> Result<R>
+where
+    D: Transact,
+    F: for<'x> FnOnce(&'x mut D::Tx<'_>) -> BoxFuture<'x, Result<R>>,
+{
+    run(db, None, f).await
+}
+
+/// run the closure in a transaction of an isolation level, savepoints keep the
+/// level of their transaction
+/// # Example
+/// ```no_run
+/// let res = crudx::transaction_with(&mut client, Isolation::Snapshot, |tx| {
+///     Box::pin(async move { Model::new(&oplog).bind(&mut **tx).find_by_id().await })
+/// })
+/// .await?;
+/// ```
+pub async fn transaction_with<D, F, R>(db: &mut D, isolation: Isolation, f: F) -> Result<R>
+where
+    D: Transact,
+    F: for<'x> FnOnce(&'x mut D::Tx<'_>) -> BoxFuture<'x, Result<R>>,
+{
+    run(db, Some(isolation), f).await
+}
+
+/// run the closure in a transaction and run the whole transaction again when it
+/// fails with a deadlock or serialization failure, so writes that are not
+/// idempotent are retried safely, the closure runs once for each attempt
+/// # Example
+/// ```no_run
+/// let res = crudx::transaction_retry(&mut pool, &Retry::new(5), Some(Isolation::Serializable), |tx| {
+///     let oplog = oplog.clone();
+///     Box::pin(async move { Model::new(&oplog).bind(&mut **tx).insert_one(None).await })
+/// })
+/// .await?;
+/// ```
+pub async fn transaction_retry<D, F, R>(
+    db: &mut D,
+    policy: &Retry,
+    isolation: Option<Isolation>,
+    mut f: F,
+) -> Result<R>
+where
+    D: Transact,
+    F: for<'x> FnMut(&'x mut D::Tx<'_>) -> BoxFuture<'x, Result<R>>,
+{
+    let mut attempts = Attempts::new(Some(*policy));
+    loop {
+        let res = run(db, isolation, &mut f).await;
+        match attempts.next(&res) {
+            Some(delay) => sleep(delay).await,
+            None => return res,
+        }
+    }
+}
+
+async fn run<D, F, R>(db: &mut D, isolation: Option<Isolation>, f: F) -> Result<R>
+where
+    D: Transact,
+    F: for<'x> FnOnce(&'x mut D::Tx<'_>) -> BoxFuture<'x, Result<R>>,
+{
+    let mut tx = db.begin(isolation).await?;
+    let res = AssertUnwindSafe(f(&mut tx)).catch_unwind().await;
+    match res {
+        Ok(Ok(value)) => {
+            D::commit(tx).await?;
+            Ok(value)
+        }
+        Ok(Err(err)) => {
+            //the error of the closure is returned even when the rollback fails,
+            //the retry of the transaction looks at it
+            let _ = D::rollback(tx).await;
+            Err(err)
+        }
+        Err(panic) => {
+            let _ = D::rollback(tx).await;
+            resume_unwind(panic)
+        }
+    }
+}